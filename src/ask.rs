@@ -0,0 +1,494 @@
+//! Ask — dialogs between libfdisk and the application.
+//! The library reports warnings and info messages and asks for numbers,
+//! offsets, strings, yes/no answers and menu choices by dialogs.
+//! The dialogs are handled by [`AskHandler`] registered on [`Context`].
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use fdisk_sys;
+use nix::errno::Errno;
+use std::collections::VecDeque;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Handler for libfdisk dialogs
+pub trait AskHandler {
+    /// Handles the dialog. The error cancels the operation which asked.
+    fn ask(&mut self, ask: Ask<'_>) -> Result<()>;
+}
+
+impl<H: AskHandler + ?Sized> AskHandler for Box<H> {
+    fn ask(&mut self, ask: Ask<'_>) -> Result<()> {
+        (**self).ask(ask)
+    }
+}
+
+impl<H: AskHandler + ?Sized> AskHandler for &mut H {
+    fn ask(&mut self, ask: Ask<'_>) -> Result<()> {
+        (**self).ask(ask)
+    }
+}
+
+/// Dialog, one variant for each FDISK_ASKTYPE_*
+pub enum Ask<'a> {
+    /// Asks for number in range
+    Number(NumberAsk<'a>),
+    /// Asks for offset, the answer may be relative or with size suffix
+    Offset(NumberAsk<'a>),
+    /// Warning with errno
+    Warn { message: String, errno: Errno },
+    /// Warning without errno
+    WarnX { message: String },
+    /// Info message
+    Info { message: String },
+    /// Asks for yes or no
+    YesNo(YesNoAsk<'a>),
+    /// Asks for string
+    String(StringAsk<'a>),
+    /// Asks for menu item
+    Menu(MenuAsk<'a>),
+    /// FDISK_ASKTYPE_NONE or type unknown to this crate
+    Unknown(i32),
+}
+
+impl<'a> Ask<'a> {
+    /// Wraps the dialog
+    ///
+    /// # Safety
+    /// `ptr` must be a valid dialog living for `'a`.
+    pub(crate) unsafe fn from_ptr(ptr: *mut fdisk_sys::fdisk_ask) -> Ask<'a> {
+        let kind = fdisk_sys::fdisk_ask_get_type(ptr);
+        match kind as u32 {
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_NUMBER => Ask::Number(NumberAsk::new(ptr)),
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_OFFSET => Ask::Offset(NumberAsk::new(ptr)),
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_WARN => Ask::Warn {
+                message: message(ptr),
+                errno: Errno::from_i32(fdisk_sys::fdisk_ask_print_get_errno(ptr)),
+            },
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_WARNX => Ask::WarnX {
+                message: message(ptr),
+            },
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_INFO => Ask::Info {
+                message: message(ptr),
+            },
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_YESNO => Ask::YesNo(YesNoAsk {
+                ptr,
+                _ask: PhantomData,
+            }),
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_STRING => Ask::String(StringAsk {
+                ptr,
+                _ask: PhantomData,
+            }),
+            fdisk_sys::fdisk_asktype_FDISK_ASKTYPE_MENU => Ask::Menu(MenuAsk {
+                ptr,
+                _ask: PhantomData,
+            }),
+            _ => Ask::Unknown(kind),
+        }
+    }
+}
+
+/// Return text of the print dialog
+unsafe fn message(ptr: *mut fdisk_sys::fdisk_ask) -> String {
+    string(fdisk_sys::fdisk_ask_print_get_mesg(ptr)).unwrap_or_default()
+}
+
+/// Return query of the dialog
+unsafe fn query(ptr: *mut fdisk_sys::fdisk_ask) -> Option<String> {
+    string(fdisk_sys::fdisk_ask_get_query(ptr))
+}
+
+unsafe fn string(src: *const libc::c_char) -> Option<String> {
+    if src.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(src).to_string_lossy().into_owned())
+}
+
+/// Number or offset dialog
+pub struct NumberAsk<'a> {
+    ptr: *mut fdisk_sys::fdisk_ask,
+    _ask: PhantomData<&'a mut fdisk_sys::fdisk_ask>,
+}
+
+impl<'a> NumberAsk<'a> {
+    fn new(ptr: *mut fdisk_sys::fdisk_ask) -> NumberAsk<'a> {
+        NumberAsk {
+            ptr,
+            _ask: PhantomData,
+        }
+    }
+
+    /// Return query, e.g. "Last sector"
+    pub fn query(&self) -> Option<String> {
+        unsafe { query(self.ptr) }
+    }
+
+    /// Return range of allowed values in string format, e.g. "1-4"
+    pub fn range(&self) -> Option<String> {
+        unsafe { string(fdisk_sys::fdisk_ask_number_get_range(self.ptr)) }
+    }
+
+    /// Return the lowest allowed value
+    pub fn low(&self) -> u64 {
+        unsafe { fdisk_sys::fdisk_ask_number_get_low(self.ptr) }
+    }
+
+    /// Return the highest allowed value
+    pub fn high(&self) -> u64 {
+        unsafe { fdisk_sys::fdisk_ask_number_get_high(self.ptr) }
+    }
+
+    /// Return default value
+    pub fn default(&self) -> u64 {
+        unsafe { fdisk_sys::fdisk_ask_number_get_default(self.ptr) }
+    }
+
+    /// Return base for relative offsets
+    pub fn base(&self) -> u64 {
+        unsafe { fdisk_sys::fdisk_ask_number_get_base(self.ptr) }
+    }
+
+    /// Return unit size in bytes, e.g. sector size for offsets in sectors
+    pub fn unit(&self) -> u64 {
+        unsafe { fdisk_sys::fdisk_ask_number_get_unit(self.ptr) }
+    }
+
+    /// Return current result
+    pub fn result(&self) -> u64 {
+        unsafe { fdisk_sys::fdisk_ask_number_get_result(self.ptr) }
+    }
+
+    /// Return true if negative numbers are interpreted relative to the high value
+    pub fn is_wrap_negative(&self) -> bool {
+        matches!(
+            unsafe { fdisk_sys::fdisk_ask_number_is_wrap_negative(self.ptr) },
+            1
+        )
+    }
+
+    /// Return true if the numbers are expressed as characters (e.g. BSD partitions "a-p")
+    pub fn is_inchars(&self) -> bool {
+        matches!(unsafe { fdisk_sys::fdisk_ask_number_inchars(self.ptr) }, 1)
+    }
+
+    /// Sets the answer
+    pub fn set_result(&self, result: u64) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_ask_number_set_result(self.ptr, result) } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("setting number result", v)),
+        }
+    }
+
+    /// Marks the answer as relative to base
+    pub fn set_relative(&self, relative: bool) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_ask_number_set_relative(self.ptr, relative as i32) } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("setting number relative", v)),
+        }
+    }
+}
+
+/// Yes/no dialog
+pub struct YesNoAsk<'a> {
+    ptr: *mut fdisk_sys::fdisk_ask,
+    _ask: PhantomData<&'a mut fdisk_sys::fdisk_ask>,
+}
+
+impl<'a> YesNoAsk<'a> {
+    /// Return query, e.g. "Do you want to remove the signature?"
+    pub fn query(&self) -> Option<String> {
+        unsafe { query(self.ptr) }
+    }
+
+    /// Return current result
+    pub fn result(&self) -> bool {
+        matches!(
+            unsafe { fdisk_sys::fdisk_ask_yesno_get_result(self.ptr) },
+            1
+        )
+    }
+
+    /// Sets the answer
+    pub fn set_result(&self, yes: bool) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_ask_yesno_set_result(self.ptr, yes as i32) } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("setting yes/no result", v)),
+        }
+    }
+}
+
+/// String dialog
+pub struct StringAsk<'a> {
+    ptr: *mut fdisk_sys::fdisk_ask,
+    _ask: PhantomData<&'a mut fdisk_sys::fdisk_ask>,
+}
+
+impl<'a> StringAsk<'a> {
+    /// Return query, e.g. "Bootstrap"
+    pub fn query(&self) -> Option<String> {
+        unsafe { query(self.ptr) }
+    }
+
+    /// Return current result
+    pub fn result(&self) -> Option<String> {
+        unsafe { string(fdisk_sys::fdisk_ask_string_get_result(self.ptr)) }
+    }
+
+    /// Sets the answer
+    pub fn set_result(&self, result: &str) -> Result<()> {
+        let result = CString::new(result.as_bytes())?;
+        // the library deallocates the result by free()
+        let ptr = unsafe { libc::strdup(result.as_ptr()) };
+        if ptr.is_null() {
+            return Err(Error::last("setting string result"));
+        }
+        match unsafe { fdisk_sys::fdisk_ask_string_set_result(self.ptr, ptr) } {
+            0 => Ok(()),
+            v => {
+                unsafe { libc::free(ptr as *mut c_void) };
+                Err(Error::fdisk("setting string result", v))
+            }
+        }
+    }
+}
+
+/// Menu item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem {
+    /// Item key, usually ASCII character (e.g. 'p' for primary partition)
+    pub key: i32,
+    pub name: String,
+    pub desc: Option<String>,
+}
+
+/// Menu dialog
+pub struct MenuAsk<'a> {
+    ptr: *mut fdisk_sys::fdisk_ask,
+    _ask: PhantomData<&'a mut fdisk_sys::fdisk_ask>,
+}
+
+impl<'a> MenuAsk<'a> {
+    /// Return query, e.g. "Partition type"
+    pub fn query(&self) -> Option<String> {
+        unsafe { query(self.ptr) }
+    }
+
+    /// Return key of the default item
+    pub fn default(&self) -> i32 {
+        unsafe { fdisk_sys::fdisk_ask_menu_get_default(self.ptr) }
+    }
+
+    /// Return menu items
+    pub fn items(&self) -> Vec<MenuItem> {
+        let n = unsafe { fdisk_sys::fdisk_ask_menu_get_nitems(self.ptr) };
+        let mut items = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut key: c_int = 0;
+            let mut name = std::ptr::null();
+            let mut desc = std::ptr::null();
+            if unsafe {
+                fdisk_sys::fdisk_ask_menu_get_item(self.ptr, i, &mut key, &mut name, &mut desc)
+            } != 0
+            {
+                break;
+            }
+            items.push(MenuItem {
+                key,
+                name: unsafe { string(name) }.unwrap_or_default(),
+                desc: unsafe { string(desc) },
+            });
+        }
+        items
+    }
+
+    /// Sets key of the selected item as the answer
+    pub fn set_result(&self, key: i32) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_ask_menu_set_result(self.ptr, key) } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("setting menu result", v)),
+        }
+    }
+}
+
+/// Calls the handler pointed by `data`
+pub(crate) unsafe extern "C" fn ask_callback<H: AskHandler>(
+    _cxt: *mut fdisk_sys::fdisk_context,
+    ask: *mut fdisk_sys::fdisk_ask,
+    data: *mut c_void,
+) -> c_int {
+    let handler = &mut *(data as *mut H);
+    // unwinding into libfdisk would abort the process
+    match panic::catch_unwind(AssertUnwindSafe(|| handler.ask(Ask::from_ptr(ask)))) {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => -(e.errno().unwrap_or(Errno::EINVAL) as i32),
+        Err(_) => -libc::EINVAL,
+    }
+}
+
+/// Restores the handler registered by set_ask_handler() on drop
+struct RestoreAsk<'a>(&'a Context);
+
+impl Drop for RestoreAsk<'_> {
+    fn drop(&mut self) {
+        let cxt = self.0;
+        unsafe {
+            match cxt.ask.is_null() {
+                true => fdisk_sys::fdisk_set_ask(cxt.ptr, None, std::ptr::null_mut()),
+                false => fdisk_sys::fdisk_set_ask(
+                    cxt.ptr,
+                    Some(ask_callback::<Box<dyn AskHandler>>),
+                    cxt.ask as *mut c_void,
+                ),
+            }
+        };
+    }
+}
+
+/// Answer for interactive operation
+pub(crate) enum Reply {
+    /// Answers the next number or offset dialog
+    Number(u64),
+    /// Answers the next yes/no dialog
+    YesNo(bool),
+    /// Answers the next string dialog
+    String(String),
+}
+
+/// Answers questions of interactive operation in order, the messages are
+/// passed to the handler registered by set_ask_handler()
+struct Replies {
+    replies: VecDeque<Reply>,
+    registered: *mut Box<dyn AskHandler>,
+    /// The first failed answer, libfdisk ignores some of them
+    error: Option<Error>,
+}
+
+impl AskHandler for Replies {
+    fn ask(&mut self, ask: Ask<'_>) -> Result<()> {
+        self.answer(ask).map_err(|e| {
+            let errno = e.errno().unwrap_or(Errno::EINVAL);
+            self.error.get_or_insert(e);
+            Error::fdisk("answering dialog", errno as i32)
+        })
+    }
+}
+
+impl Replies {
+    fn answer(&mut self, ask: Ask<'_>) -> Result<()> {
+        match ask {
+            Ask::Info { .. } | Ask::Warn { .. } | Ask::WarnX { .. } => {
+                if !self.registered.is_null() {
+                    unsafe { (*self.registered).ask(ask)? };
+                }
+                Ok(())
+            }
+            Ask::Number(n) | Ask::Offset(n) => {
+                let value = match self.replies.pop_front() {
+                    Some(Reply::Number(v)) => v,
+                    _ => return Err(Error::NotFound("number answer")),
+                };
+                if value < n.low() || value > n.high() {
+                    return Err(Error::InvalidArgument {
+                        name: "answer",
+                        value: format!("{} ({}-{})", value, n.low(), n.high()),
+                    });
+                }
+                n.set_result(value)
+            }
+            Ask::YesNo(y) => match self.replies.pop_front() {
+                Some(Reply::YesNo(v)) => y.set_result(v),
+                _ => Err(Error::NotFound("yes/no answer")),
+            },
+            Ask::String(s) => match self.replies.pop_front() {
+                Some(Reply::String(v)) => s.set_result(&v),
+                _ => Err(Error::NotFound("string answer")),
+            },
+            _ => Err(Error::NotFound("answer")),
+        }
+    }
+}
+
+impl Context {
+    /// Runs interactive operation `f`, the questions are answered by `replies`
+    /// in order. The registered handler is restored when `f` returns.
+    pub(crate) fn with_replies<R, F>(&self, replies: Vec<Reply>, f: F) -> Result<R>
+    where
+        F: FnOnce() -> Result<R>,
+    {
+        let mut handler = Replies {
+            replies: replies.into(),
+            registered: self.ask,
+            error: None,
+        };
+        let res = self.with_handler(&mut handler, f);
+        if let Some(e) = handler.error {
+            return Err(e);
+        }
+        if res.is_ok() && !handler.replies.is_empty() {
+            return Err(Error::NotFound("dialog for the answer"));
+        }
+        res
+    }
+
+    /// Calls `f` with temporary dialogs handler, the registered handler
+    /// is restored afterwards, also on panic.
+    pub(crate) fn with_handler<H, R, F>(&self, handler: &mut H, f: F) -> Result<R>
+    where
+        H: AskHandler,
+        F: FnOnce() -> Result<R>,
+    {
+        let rc = unsafe {
+            fdisk_sys::fdisk_set_ask(
+                self.ptr,
+                Some(ask_callback::<H>),
+                handler as *mut H as *mut c_void,
+            )
+        };
+        if rc != 0 {
+            return Err(self.error("setting ask handler", rc));
+        }
+        let _restore = RestoreAsk(self);
+        f()
+    }
+
+    /// Registers handler for dialogs, replaces the previous handler.
+    pub fn set_ask_handler<H: AskHandler + 'static>(&mut self, handler: H) -> Result<()> {
+        self.clear_ask_handler()?;
+        let data: Box<Box<dyn AskHandler>> = Box::new(Box::new(handler));
+        let data = Box::into_raw(data);
+        match unsafe {
+            fdisk_sys::fdisk_set_ask(
+                self.ptr,
+                Some(ask_callback::<Box<dyn AskHandler>>),
+                data as *mut c_void,
+            )
+        } {
+            0 => {
+                self.ask = data;
+                Ok(())
+            }
+            v => {
+                drop(unsafe { Box::from_raw(data) });
+                Err(self.error("setting ask handler", v))
+            }
+        }
+    }
+
+    /// Unregisters handler for dialogs, the dialogs fail with EINVAL.
+    pub fn clear_ask_handler(&mut self) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_set_ask(self.ptr, None, std::ptr::null_mut()) } {
+            0 => {
+                if !self.ask.is_null() {
+                    drop(unsafe { Box::from_raw(self.ask) });
+                    self.ask = std::ptr::null_mut();
+                }
+                self.diagnostics = None;
+                Ok(())
+            }
+            v => Err(self.error("clearing ask handler", v)),
+        }
+    }
+}
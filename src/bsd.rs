@@ -0,0 +1,281 @@
+//! BSD — label specific functions for BSD disklabel. The label lives inside
+//! the first primary DOS partition with *BSD type and it's handled by nested
+//! context, see create_bsd_disklabel() and bsd_disklabel(). The BSD partitions
+//! are added and removed by add_partition() and delete_partition() of the
+//! nested context.
+
+use crate::ask::{Ask, AskHandler, Reply};
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::label::DiskLabel;
+use crate::labelitem::LabelItem;
+use fdisk_sys;
+
+/// DOS partition types of FreeBSD, OpenBSD and NetBSD
+const BSD_DOS_TYPES: [u32; 3] = [0xa5, 0xa6, 0xa9];
+
+/// Dialogs handler recording ranges of number dialogs, the dialogs fail
+struct Ranges(Vec<(u64, u64)>);
+
+impl AskHandler for Ranges {
+    fn ask(&mut self, ask: Ask<'_>) -> Result<()> {
+        match ask {
+            Ask::Number(n) | Ask::Offset(n) => {
+                self.0.push((n.low(), n.high()));
+                Err(Error::NotFound("answer"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// BSD disklabel parameters, changed by bsd_edit_disklabel()
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BsdGeometry {
+    /// Bytes per sector, at least 512
+    #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+    pub secsize: u32,
+    /// Sectors per track, derived from sectors per cylinder by bsd_geometry()
+    #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+    pub nsectors: u32,
+    /// Tracks per cylinder
+    #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+    pub ntracks: u32,
+    /// Number of cylinders
+    #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+    pub ncylinders: u32,
+    /// Sectors per cylinder
+    pub secpercyl: u32,
+    /// Disk rotational speed
+    pub rpm: u16,
+    /// Hardware sector interleave
+    pub interleave: u16,
+    /// Sector 0 skew per track
+    pub trackskew: u16,
+    /// Sector 0 skew per cylinder
+    pub cylinderskew: u16,
+    /// Head switch time in microseconds
+    pub headswitch: u32,
+    /// Track-to-track seek time in microseconds
+    pub trkseek: u32,
+}
+
+/// Return answer for fdisk_ask_partnum(), the partition numbers in the dialog are
+/// from 1. libfdisk selects the partition without asking if there is only one
+/// candidate, the answer is empty then.
+/// # Arguments
+/// * `cxt` - asked context
+/// * `partno` - partition number (0 is the first partition)
+/// * `used` - true for used partitions, false for free slots
+/// * `name` - name of the argument for errors
+fn partnum_reply(
+    cxt: &Context,
+    partno: usize,
+    used: bool,
+    name: &'static str,
+) -> Result<Vec<Reply>> {
+    let n = unsafe { fdisk_sys::fdisk_get_npartitions(cxt.ptr) };
+    let candidates: Vec<usize> = (0..n)
+        .filter(|&i| (unsafe { fdisk_sys::fdisk_is_partition_used(cxt.ptr, i) } == 1) == used)
+        .collect();
+    if !candidates.contains(&partno) {
+        return Err(Error::InvalidArgument {
+            name,
+            value: partno.to_string(),
+        });
+    }
+    match candidates.len() {
+        1 => Ok(Vec::new()),
+        _ => Ok(vec![Reply::Number(partno as u64 + 1)]),
+    }
+}
+
+impl Context {
+    /// Creates BSD disklabel in the DOS partition, return nested context with the
+    /// new label. The changes are in-memory only, use write_disklabel() of the
+    /// nested context to write them to the device.
+    /// # Arguments
+    /// * `partno` - DOS partition number, libfdisk uses the first primary
+    ///   partition with *BSD type (0xa5, 0xa6 or 0xa9)
+    pub fn create_bsd_disklabel(&self, partno: usize) -> Result<Context> {
+        if !self.is_labeltype(DiskLabel::Dos) {
+            return Err(Error::NotFound("DOS label"));
+        }
+        let first = self
+            .get_partitions()?
+            .iter()
+            .filter(|pa| {
+                pa.part_type()
+                    .is_some_and(|t| BSD_DOS_TYPES.contains(&t.code()))
+            })
+            .filter_map(|pa| pa.partno())
+            .filter(|&partno| partno < 4)
+            .min();
+        if first != Some(partno) {
+            return Err(Error::InvalidArgument {
+                name: "*BSD partition number",
+                value: partno.to_string(),
+            });
+        }
+        let bsd = self.new_nested("bsd")?;
+        bsd.with_replies(vec![Reply::YesNo(true)], || bsd.create_disklabel("bsd"))?;
+        Ok(bsd)
+    }
+
+    /// Return nested context with BSD disklabel of the DOS disk
+    pub fn bsd_disklabel(&self) -> Result<Context> {
+        if !self.is_labeltype(DiskLabel::Dos) {
+            return Err(Error::NotFound("DOS label"));
+        }
+        let bsd = self.new_nested("bsd")?;
+        if !bsd.has_label() {
+            return Err(Error::NotFound("BSD label"));
+        }
+        Ok(bsd)
+    }
+
+    /// Return BSD disklabel parameters
+    pub fn bsd_geometry(&self) -> Result<BsdGeometry> {
+        if !self.is_labeltype(DiskLabel::Bsd) {
+            return Err(Error::NotFound("BSD label"));
+        }
+        let mut g = BsdGeometry::default();
+        for item in self.label_items()? {
+            match item {
+                LabelItem::BsdSecpercyl(v) => g.secpercyl = v as u32,
+                LabelItem::BsdRpm(v) => g.rpm = v as u16,
+                LabelItem::BsdInterleave(v) => g.interleave = v as u16,
+                LabelItem::BsdTrackskew(v) => g.trackskew = v as u16,
+                LabelItem::BsdCylinderskew(v) => g.cylinderskew = v as u16,
+                LabelItem::BsdHeadswitch(v) => g.headswitch = v as u32,
+                LabelItem::BsdTrkseek(v) => g.trkseek = v as u32,
+                #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+                LabelItem::BsdSecsize(v) => g.secsize = v as u32,
+                #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+                LabelItem::BsdNtracks(v) => g.ntracks = v as u32,
+                #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+                LabelItem::BsdCylinders(v) => g.ncylinders = v as u32,
+                _ => {}
+            }
+        }
+        // libfdisk does not provide sectors per track
+        #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+        {
+            g.nsectors = g.secpercyl.checked_div(g.ntracks).unwrap_or(0);
+        }
+        Ok(g)
+    }
+
+    /// Changes BSD disklabel parameters. All values are checked against the
+    /// ranges of libfdisk dialogs first, the label is not changed on error.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `g` - new parameters, the sectors per cylinder are limited by
+    ///   sectors per track and tracks per cylinder, rpm and interleave
+    ///   have to be at least 1. The skew, head switch and seek values
+    ///   may be 0 only if the current value is 0, libfdisk asks for at
+    ///   least 1 otherwise.
+    pub fn bsd_edit_disklabel(&self, g: &BsdGeometry) -> Result<()> {
+        if !self.is_labeltype(DiskLabel::Bsd) {
+            return Err(Error::NotFound("BSD label"));
+        }
+        // the dialogs are answered in the order of fdisk_bsd_edit_disklabel()
+        let mut fields: Vec<(&'static str, u64)> = Vec::new();
+        #[cfg(any(target_arch = "alpha", target_arch = "ia64"))]
+        fields.extend([
+            ("bytes per sector", g.secsize.into()),
+            ("sectors per track", g.nsectors.into()),
+            ("tracks per cylinder", g.ntracks.into()),
+            ("cylinders", g.ncylinders.into()),
+        ]);
+        fields.extend([
+            ("sectors per cylinder", g.secpercyl.into()),
+            ("rpm", g.rpm.into()),
+            ("interleave", g.interleave.into()),
+            ("trackskew", g.trackskew.into()),
+            ("cylinderskew", g.cylinderskew.into()),
+            ("headswitch", g.headswitch.into()),
+            ("track-to-track seek", g.trkseek.into()),
+        ]);
+        let ranges = self.bsd_edit_ranges()?;
+        if ranges.len() != fields.len() {
+            return Err(Error::NotFound("BSD disklabel dialogs"));
+        }
+        for (&(name, value), &(low, high)) in fields.iter().zip(&ranges) {
+            if value < low || value > high {
+                return Err(Error::InvalidArgument {
+                    name,
+                    value: format!("{} ({}-{})", value, low, high),
+                });
+            }
+        }
+        let replies = fields.iter().map(|&(_, v)| Reply::Number(v)).collect();
+        self.with_replies(replies, || {
+            match unsafe { fdisk_sys::fdisk_bsd_edit_disklabel(self.ptr) } {
+                0 => Ok(()),
+                v => Err(self.error("editing BSD disk label", v)),
+            }
+        })
+    }
+
+    /// Return ranges of fdisk_bsd_edit_disklabel() dialogs. The dialogs fail,
+    /// so libfdisk keeps the current values.
+    fn bsd_edit_ranges(&self) -> Result<Vec<(u64, u64)>> {
+        let mut handler = Ranges(Vec::new());
+        self.with_handler(&mut handler, || {
+            match unsafe { fdisk_sys::fdisk_bsd_edit_disklabel(self.ptr) } {
+                0 => Ok(()),
+                v => Err(self.error("editing BSD disk label", v)),
+            }
+        })?;
+        Ok(handler.0)
+    }
+
+    /// Links the DOS partition to the BSD partition, the BSD partition
+    /// gets start, size and type of the DOS partition.
+    /// # Arguments
+    /// * `parent` - parent context with DOS label
+    /// * `dos_partno` - DOS partition number (0 is the first partition)
+    /// * `bsd_partno` - unused BSD partition number (0 is the partition 'a')
+    pub fn bsd_link_partition(
+        &self,
+        parent: &Context,
+        dos_partno: usize,
+        bsd_partno: usize,
+    ) -> Result<()> {
+        // libfdisk asserts the label type
+        if !self.is_labeltype(DiskLabel::Bsd) {
+            return Err(Error::NotFound("BSD label"));
+        }
+        if unsafe { fdisk_sys::fdisk_get_parent(self.ptr) } != parent.ptr {
+            return Err(Error::NotFound("parent context"));
+        }
+        // the DOS partition is asked by the parent context
+        let dos = partnum_reply(parent, dos_partno, true, "DOS partition number")?;
+        let bsd = partnum_reply(self, bsd_partno, false, "unused BSD partition number")?;
+        parent.with_replies(dos, || {
+            self.with_replies(bsd, || {
+                match unsafe { fdisk_sys::fdisk_bsd_link_partition(self.ptr) } {
+                    0 => Ok(()),
+                    v => Err(self.error("linking BSD partition", v)),
+                }
+            })
+        })
+    }
+
+    /// Writes BSD bootstrap, the boot code is read from
+    /// /usr/ucb/mdec/<name>boot and /usr/ucb/mdec/boot<name>.
+    /// # Arguments
+    /// * `name` - disk type, e.g. "wd" or "sd"
+    pub fn bsd_write_bootstrap(&self, name: &str) -> Result<()> {
+        if !self.is_labeltype(DiskLabel::Bsd) {
+            return Err(Error::NotFound("BSD label"));
+        }
+        self.with_replies(vec![Reply::String(name.to_string())], || {
+            match unsafe { fdisk_sys::fdisk_bsd_write_bootstrap(self.ptr) } {
+                0 => Ok(()),
+                v => Err(self.error("writing BSD bootstrap", v)),
+            }
+        })
+    }
+}
@@ -0,0 +1,228 @@
+//! ChromeOS — A/B boot attributes of ChromeOS kernel partitions. The boot
+//! loader picks the slot with the highest priority which is either
+//! successful or has some tries remaining.
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::gpt::GptAttributes;
+
+/// Bits 48-51 of GPT attributes
+const PRIORITY_SHIFT: u32 = 0;
+/// Bits 52-55 of GPT attributes
+const TRIES_SHIFT: u32 = 4;
+/// Bit 56 of GPT attributes
+const SUCCESSFUL_SHIFT: u32 = 8;
+
+/// The highest priority and number of tries
+pub const CHROMEOS_MAX: u8 = 15;
+
+/// A/B boot attributes stored in GPT attribute bits 48-56
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChromeOsAttributes {
+    /// Boot priority 0-15, zero means the slot is not bootable
+    pub priority: u8,
+    /// Remaining boot attempts 0-15
+    pub tries: u8,
+    /// The slot booted successfully
+    pub successful: bool,
+}
+
+impl ChromeOsAttributes {
+    /// Return attributes with the A/B bits replaced, other bits are kept.
+    /// Fails if priority or tries is greater than 15.
+    pub fn apply(&self, attrs: GptAttributes) -> Result<GptAttributes> {
+        if self.priority > CHROMEOS_MAX {
+            return Err(Error::InvalidArgument {
+                name: "ChromeOS priority",
+                value: self.priority.to_string(),
+            });
+        }
+        if self.tries > CHROMEOS_MAX {
+            return Err(Error::InvalidArgument {
+                name: "ChromeOS tries",
+                value: self.tries.to_string(),
+            });
+        }
+        let bits = u16::from(self.priority) << PRIORITY_SHIFT
+            | u16::from(self.tries) << TRIES_SHIFT
+            | u16::from(self.successful) << SUCCESSFUL_SHIFT;
+        let mut attrs = attrs;
+        attrs.set_type_specific(attrs.type_specific() & !0x1ff | bits);
+        Ok(attrs)
+    }
+}
+
+impl From<GptAttributes> for ChromeOsAttributes {
+    fn from(attrs: GptAttributes) -> ChromeOsAttributes {
+        let bits = attrs.type_specific();
+        ChromeOsAttributes {
+            priority: (bits >> PRIORITY_SHIFT & 0xf) as u8,
+            tries: (bits >> TRIES_SHIFT & 0xf) as u8,
+            successful: bits >> SUCCESSFUL_SHIFT & 1 == 1,
+        }
+    }
+}
+
+impl Context {
+    /// Return A/B boot attributes of the GPT partition
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    pub fn chromeos_attrs(&self, partno: usize) -> Result<ChromeOsAttributes> {
+        Ok(self.gpt_partition_attrs(partno)?.into())
+    }
+
+    /// Sets A/B boot attributes of the GPT partition, other attribute bits are kept.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    /// * `ab` - new A/B attributes
+    pub fn set_chromeos_attrs(&self, partno: usize, ab: ChromeOsAttributes) -> Result<()> {
+        let attrs = ab.apply(self.gpt_partition_attrs(partno)?)?;
+        self.set_gpt_partition_attrs(partno, attrs)
+    }
+
+    /// Marks `slot` as the slot to boot next and writes the disklabel. The slot
+    /// gets priority higher than `other` and `tries` boot attempts, the successful
+    /// flag is cleared. The priority of `other` is lowered if `slot` can't get
+    /// higher priority. Nothing is written if any of the slots can't be updated.
+    /// # Arguments
+    /// * `slot` - partition number of the updated slot
+    /// * `other` - partition number of the other slot
+    /// * `tries` - boot attempts (1-15) before the boot loader falls back to `other`
+    pub fn mark_slot_active(&self, slot: usize, other: usize, tries: u8) -> Result<()> {
+        if slot == other {
+            return Err(Error::InvalidArgument {
+                name: "other slot",
+                value: other.to_string(),
+            });
+        }
+        // the slot without tries and successful flag is not bootable
+        if tries == 0 {
+            return Err(Error::InvalidArgument {
+                name: "ChromeOS tries",
+                value: tries.to_string(),
+            });
+        }
+        let cur = self.gpt_partition_attrs(slot)?;
+        let alt = self.gpt_partition_attrs(other)?;
+        let mut other_ab = ChromeOsAttributes::from(alt);
+        if other_ab.priority == CHROMEOS_MAX {
+            other_ab.priority -= 1;
+        }
+        let slot_ab = ChromeOsAttributes {
+            priority: other_ab.priority + 1,
+            tries,
+            successful: false,
+        };
+        self.update_slots(&[
+            (slot, cur, slot_ab.apply(cur)?),
+            (other, alt, other_ab.apply(alt)?),
+        ])
+    }
+
+    /// Marks `slot` as successfully booted and writes the disklabel. The slot
+    /// gets the successful flag and zero tries, the priority is kept.
+    /// # Arguments
+    /// * `slot` - partition number of the booted slot
+    pub fn mark_slot_good(&self, slot: usize) -> Result<()> {
+        let cur = self.gpt_partition_attrs(slot)?;
+        let mut ab = ChromeOsAttributes::from(cur);
+        ab.tries = 0;
+        ab.successful = true;
+        self.update_slots(&[(slot, cur, ab.apply(cur)?)])
+    }
+
+    /// Sets new attributes of the slots and writes the disklabel,
+    /// the old attributes are restored on error.
+    fn update_slots(&self, slots: &[(usize, GptAttributes, GptAttributes)]) -> Result<()> {
+        let mut res = Ok(());
+        for &(partno, _, new) in slots {
+            res = self.set_gpt_partition_attrs(partno, new);
+            if res.is_err() {
+                break;
+            }
+        }
+        if res.is_ok() {
+            res = self.write_disklabel();
+        }
+        if res.is_err() {
+            for &(partno, old, _) in slots {
+                let _ = self.set_gpt_partition_attrs(partno, old);
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_sets_bits_48_56() {
+        let ab = ChromeOsAttributes {
+            priority: 0xa,
+            tries: 0x5,
+            successful: true,
+        };
+        let attrs = ab.apply(GptAttributes::empty()).unwrap();
+        assert_eq!(attrs.bits(), 0x15a << 48);
+        assert_eq!(ChromeOsAttributes::from(attrs), ab);
+    }
+
+    #[test]
+    fn round_trip() {
+        for priority in 0..=CHROMEOS_MAX {
+            for tries in 0..=CHROMEOS_MAX {
+                for successful in [false, true] {
+                    let ab = ChromeOsAttributes {
+                        priority,
+                        tries,
+                        successful,
+                    };
+                    let attrs = ab.apply(GptAttributes::empty()).unwrap();
+                    assert_eq!(ChromeOsAttributes::from(attrs), ab);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_keeps_other_bits() {
+        let other = !(0x1ff_u64 << 48);
+        let attrs = GptAttributes::from_bits_retain(other);
+        let ab = ChromeOsAttributes {
+            priority: CHROMEOS_MAX,
+            tries: CHROMEOS_MAX,
+            successful: true,
+        };
+        let attrs = ab.apply(attrs).unwrap();
+        assert_eq!(attrs.bits() & other, other);
+        assert_eq!(ChromeOsAttributes::from(attrs), ab);
+        let attrs = ChromeOsAttributes::default().apply(attrs).unwrap();
+        assert_eq!(attrs.bits(), other);
+    }
+
+    #[test]
+    fn from_ignores_other_bits() {
+        let attrs = GptAttributes::from_bits_retain(!(0x1ff_u64 << 48));
+        assert_eq!(
+            ChromeOsAttributes::from(attrs),
+            ChromeOsAttributes::default()
+        );
+    }
+
+    #[test]
+    fn apply_rejects_out_of_range() {
+        let ab = ChromeOsAttributes {
+            priority: CHROMEOS_MAX + 1,
+            ..Default::default()
+        };
+        assert!(ab.apply(GptAttributes::empty()).is_err());
+        let ab = ChromeOsAttributes {
+            tries: CHROMEOS_MAX + 1,
+            ..Default::default()
+        };
+        assert!(ab.apply(GptAttributes::empty()).is_err());
+    }
+}
@@ -1,11 +1,15 @@
 //! Stores info about device, labels etc.
 
+use crate::ask::AskHandler;
+use crate::diagnostic::Diagnostic;
+use crate::error::{Error, Result};
 use crate::table::Table;
-use anyhow::{anyhow, Result};
 use fdisk_sys;
-use std::ffi::{CStr, CString};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, OsStr};
 use std::os::unix::ffi::OsStrExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 #[repr(u32)]
 pub enum DiskUnit {
@@ -16,6 +20,10 @@ pub enum DiskUnit {
 /// Stores info about device
 pub struct Context {
     pub(crate) ptr: *mut fdisk_sys::fdisk_context,
+    /// Dialogs handler registered by set_ask_handler()
+    pub(crate) ask: *mut Box<dyn AskHandler>,
+    /// Messages collected by enable_diagnostics()
+    pub(crate) diagnostics: Option<Rc<RefCell<Vec<Diagnostic>>>>,
 }
 
 impl Context {
@@ -23,6 +31,8 @@ impl Context {
     pub fn new() -> Context {
         Context {
             ptr: unsafe { fdisk_sys::fdisk_new_context() },
+            ask: std::ptr::null_mut(),
+            diagnostics: None,
         }
     }
 
@@ -38,12 +48,15 @@ impl Context {
         let name = CString::new(name.as_bytes())?;
         let ptr = unsafe { fdisk_sys::fdisk_new_nested_context(self.ptr, name.as_ptr()) };
         if ptr.is_null() {
-            return Err(anyhow!(
-                "nested context is null, errno: {}",
-                nix::Error::last()
-            ));
+            return Err(Error::last("creating nested context").with_device(self.device()));
         }
-        Ok(Context { ptr })
+        // the nested context inherits dialogs handler owned by this context
+        unsafe { fdisk_sys::fdisk_set_ask(ptr, None, std::ptr::null_mut()) };
+        Ok(Context {
+            ptr,
+            ask: std::ptr::null_mut(),
+            diagnostics: None,
+        })
     }
 
     /// Increments reference counter.
@@ -58,18 +71,11 @@ impl Context {
     /// * `readonly` - how to open the device
     pub fn assign_device<P: AsRef<Path>>(&self, name: P, readonly: bool) -> Result<()> {
         let name = name.as_ref();
-        let device = match CString::new(name.as_os_str().as_bytes()) {
-            Ok(s) => s,
-            _ => return Err(anyhow!("converting to CString {}", name.display())),
-        };
+        let device = CString::new(name.as_os_str().as_bytes())?;
         match unsafe { fdisk_sys::fdisk_assign_device(self.ptr, device.as_ptr(), readonly as i32) }
         {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "assigning device {}, errno: {}",
-                name.display(),
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(Error::fdisk("assigning device", v).with_device(Some(name.to_path_buf()))),
         }
     }
 
@@ -80,10 +86,16 @@ impl Context {
     pub fn deassign_device(&self, nosync: bool) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_deassign_device(self.ptr, nosync as i32) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "closing device, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("closing device", v)),
+        }
+    }
+
+    /// Close the device and open it again, the disklabel is probed again
+    /// and the in-memory changes are discarded.
+    pub fn reassign_device(&self) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_reassign_device(self.ptr) } {
+            0 => Ok(()),
+            v => Err(self.error("reassigning device", v)),
         }
     }
 
@@ -93,10 +105,7 @@ impl Context {
     pub fn enable_wipe(&self, enable: bool) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_enable_wipe(self.ptr, enable as i32) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "changing wipe, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("changing wipe", v)),
         }
     }
 
@@ -107,10 +116,7 @@ impl Context {
     pub fn enable_bootbits_protection(&self, enable: bool) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_enable_bootbits_protection(self.ptr, enable as i32) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "changing bootbits protection, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("changing bootbits protection", v)),
         }
     }
 
@@ -121,10 +127,7 @@ impl Context {
     pub fn enable_details(&self, enable: bool) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_enable_details(self.ptr, enable as i32) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "changing details, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("changing details", v)),
         }
     }
 
@@ -134,10 +137,7 @@ impl Context {
     pub fn enable_listonly(&self, enable: bool) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_enable_listonly(self.ptr, enable as i32) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "changing listonly, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("changing listonly", v)),
         }
     }
 
@@ -158,15 +158,30 @@ impl Context {
         unsafe {
             let src = fdisk_sys::fdisk_get_devname(self.ptr);
             if src.is_null() {
-                return Err(anyhow!("no valid name"));
+                return Err(Error::NotFound("device name"));
             }
-            match CStr::from_ptr(src).to_str() {
-                Ok(v) => Ok(v.to_string()),
-                Err(e) => Err(e.into()),
+            Ok(CStr::from_ptr(src).to_str()?.to_string())
+        }
+    }
+
+    /// Return path of the assigned device
+    pub(crate) fn device(&self) -> Option<PathBuf> {
+        unsafe {
+            let src = fdisk_sys::fdisk_get_devname(self.ptr);
+            if src.is_null() {
+                return None;
             }
+            Some(PathBuf::from(OsStr::from_bytes(
+                CStr::from_ptr(src).to_bytes(),
+            )))
         }
     }
 
+    /// Builds error for the failed operation on the assigned device
+    pub(crate) fn error(&self, op: &'static str, rc: i32) -> Error {
+        Error::fdisk(op, rc).with_device(self.device())
+    }
+
     /// Return first possible LBA on disk for data partitions.
     pub fn first_lba(&self) -> u64 {
         unsafe { fdisk_sys::fdisk_get_first_lba(self.ptr) }
@@ -221,7 +236,13 @@ impl Context {
             if ptr.is_null() {
                 return None;
             }
-            Some(Context { ptr })
+            // the wrapper unrefs the context on drop
+            fdisk_sys::fdisk_ref_context(ptr);
+            Some(Context {
+                ptr,
+                ask: std::ptr::null_mut(),
+                diagnostics: None,
+            })
         }
     }
 
@@ -240,7 +261,7 @@ impl Context {
         let mut table = Table::new();
         match unsafe { fdisk_sys::fdisk_get_partitions(self.ptr, &mut table.ptr) } {
             0 => Ok(table),
-            v => Err(anyhow!("{}", nix::errno::from_i32(-v))),
+            v => Err(self.error("getting partitions", v)),
         }
     }
 
@@ -261,12 +282,9 @@ impl Context {
         unsafe {
             let src = fdisk_sys::fdisk_get_unit(self.ptr, n as i32);
             if src.is_null() {
-                return Err(anyhow!("no valid name"));
-            }
-            match CStr::from_ptr(src).to_str() {
-                Ok(v) => Ok(v.to_string()),
-                Err(e) => Err(e.into()),
+                return Err(Error::NotFound("unit name"));
             }
+            Ok(CStr::from_ptr(src).to_str()?.to_string())
         }
     }
 
@@ -312,7 +330,8 @@ impl Context {
     pub fn set_first_lba(&self, lba: u64) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_set_first_lba(self.ptr, lba) } {
             0 => Ok(()),
-            v => Err(anyhow!("fdisk_set_first_lba failed with code {}", v)),
+            // libfdisk returns negative errno as fdisk_sector_t, the low bits keep it
+            v => Err(self.error("setting first LBA", v as i32)),
         }
     }
 
@@ -327,7 +346,7 @@ impl Context {
     pub fn set_last_lba(&self, lba: u64) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_set_last_lba(self.ptr, lba) } {
             0 => Ok(()),
-            v => Err(anyhow!("fdisk_set_last_lba failed with code {}", v)),
+            v => Err(self.error("setting last LBA", v)),
         }
     }
 
@@ -337,7 +356,7 @@ impl Context {
     pub fn set_size_unit(&self, unit: DiskUnit) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_set_size_unit(self.ptr, unit as i32) } {
             0 => Ok(()),
-            v => Err(anyhow!("{}", nix::errno::from_i32(-v))),
+            v => Err(self.error("setting size unit", v)),
         }
     }
 
@@ -352,7 +371,7 @@ impl Context {
         };
         match unsafe { fdisk_sys::fdisk_set_unit(self.ptr, s.as_ptr()) } {
             0 => Ok(()),
-            v => Err(anyhow!("{}", nix::errno::from_i32(-v))),
+            v => Err(self.error("setting unit", v)),
         }
     }
 
@@ -369,16 +388,20 @@ impl Context {
     pub fn save_user_sector_size(&self, phy: u32, log: u32) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_save_user_sector_size(self.ptr, phy, log) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "saving sector size, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("saving sector size", v)),
         }
     }
 }
 
 impl Drop for Context {
     fn drop(&mut self) {
+        if !self.ask.is_null() {
+            // the library context may outlive this wrapper
+            unsafe {
+                fdisk_sys::fdisk_set_ask(self.ptr, None, std::ptr::null_mut());
+                drop(Box::from_raw(self.ask));
+            }
+        }
         unsafe { fdisk_sys::fdisk_unref_context(self.ptr) }
     }
 }
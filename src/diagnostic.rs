@@ -0,0 +1,90 @@
+//! Diagnostic — warnings and info messages reported by libfdisk.
+//! The messages are collected in non-interactive mode enabled by
+//! [`Context::enable_diagnostics`] instead of being printed by the library.
+
+use crate::ask::{Ask, AskHandler};
+use crate::context::Context;
+use crate::error::{Error, Result};
+use nix::errno::Errno;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// Severity of the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// FDISK_ASKTYPE_INFO
+    Info,
+    /// FDISK_ASKTYPE_WARN or FDISK_ASKTYPE_WARNX
+    Warning,
+}
+
+/// Message reported by libfdisk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Severity of the message
+    pub severity: Severity,
+    /// Message text, e.g. "Partition 1 overlaps with partition 2."
+    pub message: String,
+    /// Error code reported with FDISK_ASKTYPE_WARN
+    pub errno: Option<Errno>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errno {
+            Some(errno) => write!(f, "{}: {}", self.message, errno.desc()),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Non-interactive dialogs handler collecting the messages
+struct Collector(Rc<RefCell<Vec<Diagnostic>>>);
+
+impl Collector {
+    fn push(&self, severity: Severity, message: String, errno: Option<Errno>) {
+        self.0.borrow_mut().push(Diagnostic {
+            severity,
+            message,
+            errno,
+        });
+    }
+}
+
+impl AskHandler for Collector {
+    fn ask(&mut self, ask: Ask<'_>) -> Result<()> {
+        match ask {
+            Ask::Info { message } => self.push(Severity::Info, message, None),
+            Ask::Warn { message, errno } => self.push(Severity::Warning, message, Some(errno)),
+            Ask::WarnX { message } => self.push(Severity::Warning, message, None),
+            Ask::Number(n) | Ask::Offset(n) => n.set_result(n.default())?,
+            Ask::Menu(m) => m.set_result(m.default())?,
+            Ask::YesNo(y) => y.set_result(false)?,
+            Ask::String(_) | Ask::Unknown(_) => return Err(Error::NotFound("answer")),
+        }
+        Ok(())
+    }
+}
+
+impl Context {
+    /// Enables non-interactive mode, warnings and info messages are collected
+    /// and returned by take_diagnostics(). Questions are answered by defaults,
+    /// yes/no questions by "no" and string questions fail with EINVAL.
+    /// The handler registered by set_ask_handler() is replaced.
+    pub fn enable_diagnostics(&mut self) -> Result<()> {
+        let diagnostics = Rc::new(RefCell::new(Vec::new()));
+        self.set_ask_handler(Collector(Rc::clone(&diagnostics)))?;
+        self.diagnostics = Some(diagnostics);
+        Ok(())
+    }
+
+    /// Return messages collected since the last call, empty if
+    /// enable_diagnostics() is not active.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        match &self.diagnostics {
+            Some(diagnostics) => diagnostics.take(),
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! DOS — label specific functions for MBR partition table with extended
+//! partition. The logical partitions are described by chain of EBRs
+//! (extended boot records) inside the extended partition.
+
+use crate::ask::Reply;
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::label::{DiskLabel, Label};
+use crate::partition::Partition;
+use fdisk_sys;
+
+/// DOS partition type of extended partition
+const DOS_EXTENDED_TYPE: u32 = 0x05;
+
+/// Number of primary partitions, the logical partitions are numbered from 4
+pub const DOS_MAX_PRIMARY: usize = 4;
+
+/// Primary or extended partition with logical partitions
+pub struct DosPartition {
+    /// Primary or extended partition
+    pub partition: Partition,
+    /// Logical partitions in EBR chain order, empty for primary partition
+    pub logical: Vec<Partition>,
+}
+
+impl DosPartition {
+    /// Return true if the partition is extended partition
+    pub fn is_extended(&self) -> bool {
+        self.partition.is_container()
+    }
+}
+
+impl Context {
+    /// Adds extended partition, return its number.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `partno` - primary partition number (0-3)
+    /// * `start` - first sector
+    /// * `size` - number of sectors
+    pub fn dos_add_extended(&self, partno: usize, start: u64, size: u64) -> Result<usize> {
+        if partno >= DOS_MAX_PRIMARY {
+            return Err(Error::InvalidArgument {
+                name: "primary partition number",
+                value: partno.to_string(),
+            });
+        }
+        let label = self.dos_label()?;
+        let t = label
+            .parttype_from_code(DOS_EXTENDED_TYPE)
+            .ok_or(Error::NotFound("extended partition type"))?;
+        let pa = Partition::new();
+        pa.set_partno(partno)?;
+        pa.set_start(start)?;
+        pa.set_size(size)?;
+        pa.set_type(&t)?;
+        self.add_partition(&pa)
+    }
+
+    /// Adds logical partition at the end of EBR chain, return its number.
+    /// Missing start and size are set to defaults, the partition number of
+    /// the template is set to the next logical partition.
+    /// # Arguments
+    /// * `pa` - partition template
+    pub fn dos_add_logical(&self, pa: &Partition) -> Result<usize> {
+        self.dos_label()?;
+        let partno = self
+            .get_partitions()?
+            .iter()
+            .filter_map(|pa| pa.partno())
+            .filter(|&partno| partno >= DOS_MAX_PRIMARY)
+            .max()
+            .map_or(DOS_MAX_PRIMARY, |partno| partno + 1);
+        pa.set_partno(partno)?;
+        if pa.start().is_none() {
+            pa.start_follow_default(true)?;
+        }
+        if pa.size().is_none() {
+            pa.end_follow_default(true)?;
+        }
+        self.add_partition(pa)
+    }
+
+    /// Return primary and extended partitions, the extended partition
+    /// contains logical partitions from EBR chain.
+    pub fn dos_partition_tree(&self) -> Result<Vec<DosPartition>> {
+        self.dos_label()?;
+        let table = self.get_partitions()?;
+        let mut tree: Vec<DosPartition> = Vec::new();
+        for pa in table.iter().filter(|pa| !pa.is_nested()) {
+            tree.push(DosPartition {
+                partition: pa.to_partition(),
+                logical: Vec::new(),
+            });
+        }
+        for pa in table.iter().filter(|pa| pa.is_nested()) {
+            let parent = pa.parent()?;
+            let node = tree
+                .iter_mut()
+                .find(|node| node.partition.partno() == Some(parent))
+                .ok_or(Error::NotFound("extended partition"))?;
+            node.logical.push(pa.to_partition());
+        }
+        Ok(tree)
+    }
+
+    /// Moves begin of the partition data, the partition size is changed but
+    /// the end is kept. Useful for logical partitions where data begin
+    /// is not right after the EBR.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    /// * `start` - new first sector of data
+    pub fn dos_move_begin(&self, partno: usize, start: u64) -> Result<()> {
+        self.dos_label()?;
+        self.with_replies(vec![Reply::Number(start)], || {
+            match unsafe { fdisk_sys::fdisk_dos_move_begin(self.ptr, partno) } {
+                0 => Ok(()),
+                v => Err(self.error("moving partition begin", v)),
+            }
+        })
+    }
+
+    /// Return DOS label driver, fails if the current label is not DOS
+    fn dos_label(&self) -> Result<Label<'_>> {
+        if !self.is_labeltype(DiskLabel::Dos) {
+            return Err(Error::NotFound("DOS label"));
+        }
+        self.label().ok_or(Error::NotFound("DOS label"))
+    }
+}
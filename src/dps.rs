@@ -0,0 +1,349 @@
+//! Discoverable Partitions Specification — GPT partition types and
+//! attribute flags used by systemd to find partitions without /etc/fstab.
+
+use crate::error::Result;
+use crate::gpt::GptAttributes;
+use crate::label::Label;
+use crate::parttype::PartType;
+
+impl GptAttributes {
+    /// The partition is not mounted automatically (bit 63)
+    pub const DPS_NO_AUTO: GptAttributes = GptAttributes::TYPE_SPECIFIC_63;
+    /// The partition is mounted read-only (bit 60)
+    pub const DPS_READ_ONLY: GptAttributes = GptAttributes::TYPE_SPECIFIC_60;
+    /// The file system is grown to the partition size on mount (bit 59)
+    pub const DPS_GROW_FS: GptAttributes = GptAttributes::TYPE_SPECIFIC_59;
+
+    /// Return true if the no-auto flag is set
+    pub fn is_no_auto(&self) -> bool {
+        self.contains(GptAttributes::DPS_NO_AUTO)
+    }
+
+    /// Return true if the read-only flag is set
+    pub fn is_read_only(&self) -> bool {
+        self.contains(GptAttributes::DPS_READ_ONLY)
+    }
+
+    /// Return true if the grow-fs flag is set
+    pub fn is_grow_fs(&self) -> bool {
+        self.contains(GptAttributes::DPS_GROW_FS)
+    }
+}
+
+/// CPU architecture of architecture specific partitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Architecture {
+    X86,
+    X86_64,
+    Alpha,
+    Arc,
+    Arm,
+    Arm64,
+    Ia64,
+    LoongArch64,
+    MipsLe,
+    Mips64Le,
+    Ppc,
+    Ppc64,
+    Ppc64Le,
+    RiscV32,
+    RiscV64,
+    S390,
+    S390x,
+    TileGx,
+}
+
+impl Architecture {
+    /// All known architectures
+    pub const ALL: [Architecture; 18] = [
+        Architecture::X86,
+        Architecture::X86_64,
+        Architecture::Alpha,
+        Architecture::Arc,
+        Architecture::Arm,
+        Architecture::Arm64,
+        Architecture::Ia64,
+        Architecture::LoongArch64,
+        Architecture::MipsLe,
+        Architecture::Mips64Le,
+        Architecture::Ppc,
+        Architecture::Ppc64,
+        Architecture::Ppc64Le,
+        Architecture::RiscV32,
+        Architecture::RiscV64,
+        Architecture::S390,
+        Architecture::S390x,
+        Architecture::TileGx,
+    ];
+
+    /// Return architecture the crate is compiled for
+    pub fn native() -> Option<Architecture> {
+        if cfg!(target_arch = "x86") {
+            Some(Architecture::X86)
+        } else if cfg!(target_arch = "x86_64") {
+            Some(Architecture::X86_64)
+        } else if cfg!(target_arch = "arm") {
+            Some(Architecture::Arm)
+        } else if cfg!(target_arch = "aarch64") {
+            Some(Architecture::Arm64)
+        } else if cfg!(target_arch = "loongarch64") {
+            Some(Architecture::LoongArch64)
+        } else if cfg!(all(target_arch = "mips", target_endian = "little")) {
+            Some(Architecture::MipsLe)
+        } else if cfg!(all(target_arch = "mips64", target_endian = "little")) {
+            Some(Architecture::Mips64Le)
+        } else if cfg!(target_arch = "powerpc") {
+            Some(Architecture::Ppc)
+        } else if cfg!(all(target_arch = "powerpc64", target_endian = "big")) {
+            Some(Architecture::Ppc64)
+        } else if cfg!(all(target_arch = "powerpc64", target_endian = "little")) {
+            Some(Architecture::Ppc64Le)
+        } else if cfg!(target_arch = "riscv32") {
+            Some(Architecture::RiscV32)
+        } else if cfg!(target_arch = "riscv64") {
+            Some(Architecture::RiscV64)
+        } else if cfg!(target_arch = "s390x") {
+            Some(Architecture::S390x)
+        } else {
+            None
+        }
+    }
+
+    /// Return GUIDs of root, usr, root verity, usr verity, root verity
+    /// signature and usr verity signature partitions
+    fn guids(self) -> [&'static str; 6] {
+        match self {
+            Architecture::X86 => [
+                "44479540-F297-41B2-9AF7-D131D5F0458A",
+                "75250D76-8CC6-458E-BD66-BD47CC81A812",
+                "D13C5D3B-B5D1-422A-B29F-9454FDC89D76",
+                "8F461B0D-14EE-4E81-9AA9-049B6FB97ABD",
+                "5996FC05-109C-48DE-808B-23FA0830B676",
+                "974A71C0-DE41-43C3-BE5D-5C5CCD1AD2C0",
+            ],
+            Architecture::X86_64 => [
+                "4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709",
+                "8484680C-9521-48C6-9C11-B0720656F69E",
+                "2C7357ED-EBD2-46D9-AEC1-23D437EC2BF5",
+                "77FF5F63-E7B6-4633-ACF4-1565B864C0E6",
+                "41092B05-9FC8-4523-994F-2DEF0408B176",
+                "E7BB33FB-06CF-4E81-8273-E543B413E2E2",
+            ],
+            Architecture::Alpha => [
+                "6523F8AE-3EB1-4E2A-A05A-18B695AE656F",
+                "E18CF08C-33EC-4C0D-8246-C6C6FB3DA024",
+                "FC56D9E9-E6E5-4C06-BE32-E74407CE09A5",
+                "8CCE0D25-C0D0-4A44-BD87-46331BF1DF67",
+                "D46495B7-A053-414F-80F7-700C99921EF8",
+                "5C6E1C76-076A-457A-A0FE-F3B4CD21CE6E",
+            ],
+            Architecture::Arc => [
+                "D27F46ED-2919-4CB8-BD25-9531F3C16534",
+                "7978A683-6316-4922-BBEE-38BFF5A2FECC",
+                "24B2D975-0F97-4521-AFA1-CD531E421B8D",
+                "FCA0598C-D880-4591-8C16-4EDA05C7347C",
+                "143A70BA-CBD3-4F06-919F-6C05683A78BC",
+                "94F9A9A1-9971-427A-A400-50CB297F0F35",
+            ],
+            Architecture::Arm => [
+                "69DAD710-2CE4-4E3C-B16C-21A1D49ABED3",
+                "7D0359A3-02B3-4F0A-865C-654403E70625",
+                "7386CDF2-203C-47A9-A498-F2ECCE45A2D6",
+                "C215D751-7BCD-4649-BE90-6627490A4C05",
+                "42B0455F-EB11-491D-98D3-56145BA9D037",
+                "D7FF812F-37D1-4902-A810-D76BA57B975A",
+            ],
+            Architecture::Arm64 => [
+                "B921B045-1DF0-41C3-AF44-4C6F280D3FAE",
+                "B0E01050-EE5F-4390-949A-9101B17104E9",
+                "DF3300CE-D69F-4C92-978C-9BFB0F38D820",
+                "6E11A4E7-FBCA-4DED-B9E9-E1A512BB664E",
+                "6DB69DE6-29F4-4758-A7A5-962190F00CE3",
+                "C23CE4FF-44BD-4B00-B2D4-B41B3419E02A",
+            ],
+            Architecture::Ia64 => [
+                "993D8D3D-F80E-4225-855A-9DAF8ED7EA97",
+                "4301D2A6-4E3B-4B2A-BB94-9E0B2C4225EA",
+                "86ED10D5-B607-45BB-8957-D350F23D0571",
+                "6A491E03-3BE7-4545-8E38-83320E0EA880",
+                "E98B36EE-32BA-4882-9B12-0CE14655F46A",
+                "8DE58BC2-2A43-460D-B14E-A76E4A17B47F",
+            ],
+            Architecture::LoongArch64 => [
+                "77055800-792C-4F94-B39A-98C91B762BB6",
+                "E611C702-575C-4CBE-9A46-434FA0BF7E3F",
+                "F3393B22-E9AF-4613-A948-9D3BFBD0C535",
+                "F46B2C26-59AE-48F0-9106-C50ED47F673D",
+                "5AFB67EB-ECC8-4F85-AE8E-AC1E7C50E7D0",
+                "B024F315-D330-444C-8461-44BBDE524E99",
+            ],
+            Architecture::MipsLe => [
+                "37C58C8A-D913-4156-A25F-48B1B64E07F0",
+                "0F4868E9-9952-4706-979F-3ED3A473E947",
+                "D7D150D2-2A04-4A33-8F12-16651205FF7B",
+                "46B98D8D-B55C-4E8F-AAB3-37FCA7F80752",
+                "C919CC1F-4456-4EFF-918C-F75E94525CA5",
+                "3E23CA0B-A4BC-4B4E-8087-5AB6A26AA8A9",
+            ],
+            Architecture::Mips64Le => [
+                "700BDA43-7A34-4507-B179-EEB93D7A7CA3",
+                "C97C1F32-BA06-40B4-9F22-236061B08AA8",
+                "16B417F8-3E06-4F57-8DD2-9B5232F41AA6",
+                "3C3D61FE-B5F3-414D-BB71-8739A694A4EF",
+                "904E58EF-5C65-4A31-9C57-6AF5FC7C5DE7",
+                "F2C2C7EE-ADCC-4351-B5C6-EE9816B66E16",
+            ],
+            Architecture::Ppc => [
+                "1DE3F1EF-FA98-47B5-8DCD-4A860A654D78",
+                "7D14FEC5-CC71-415D-9D6C-06BF0B3C3EAF",
+                "98CFE649-1588-46DC-B2F0-ADD147424925",
+                "DF765D00-270E-49E5-BC75-F47BB2118B09",
+                "1B31B5AA-ADD9-463A-B2ED-BD467FC857E7",
+                "7007891D-D371-4A80-86A4-5CB875B9302E",
+            ],
+            Architecture::Ppc64 => [
+                "912ADE1D-A839-4913-8964-A10EEE08FBD2",
+                "2C9739E2-F068-46B3-9FD0-01C5A9AFBCCA",
+                "9225A9A3-3C19-4D89-B4F6-EEFF88F17631",
+                "BDB528A5-A259-475F-A87D-DA53FA736A07",
+                "F5E2C20C-45B2-4FFA-BCE9-2A60737E1AAF",
+                "0B888863-D7F8-4D9E-9766-239FCE4D58AF",
+            ],
+            Architecture::Ppc64Le => [
+                "C31C45E6-3F39-412E-80FB-4809C4980599",
+                "15BB03AF-77E7-4D4A-B12B-C0D084F7491C",
+                "906BD944-4589-4AAE-A4E4-DD983917446A",
+                "EE2B9983-21E8-4153-86D9-B6901A54D1CE",
+                "D4A236E7-E873-4C07-BF1D-BF6CF7F1C3C6",
+                "C8BFBD1E-268E-4521-8BBA-BF314C399557",
+            ],
+            Architecture::RiscV32 => [
+                "60D5A7FE-8E7D-435C-B714-3DD8162144E1",
+                "B933FB22-5C3F-4F91-AF90-E2BB0FA50702",
+                "AE0253BE-1167-4007-AC68-43926C14C5DE",
+                "CB1EE4E3-8CD0-4136-A0A4-AA61A32E8730",
+                "3A112A75-8729-4380-B4CF-764D79934448",
+                "C3836A13-3137-45BA-B583-B16C50FE5EB4",
+            ],
+            Architecture::RiscV64 => [
+                "72EC70A6-CF74-40E6-BD49-4BDA08E8F224",
+                "BEAEC34B-8442-439B-A40B-984381ED097D",
+                "B6ED5582-440B-4209-B8DA-5FF7C419EA3D",
+                "8F1056BE-9B05-47C4-81D6-BE53128E5B54",
+                "EFE0F087-EA8D-4469-821A-4C2A96A8386A",
+                "D2F9000A-7A18-453F-B5CD-4D32F77A7B32",
+            ],
+            Architecture::S390 => [
+                "08A7ACEA-624C-4A20-91E8-6E0FA67D23F9",
+                "CD0F869B-D0FB-4CA0-B141-9EA87CC78D66",
+                "7AC63B47-B25C-463B-8DF8-B4A94E6C90E1",
+                "B663C618-E7BC-4D6D-90AA-11B756BB1797",
+                "3482388E-4254-435A-A241-766A065F9960",
+                "17440E4F-A8D0-467F-A46E-3912AE6EF2C5",
+            ],
+            Architecture::S390x => [
+                "5EEAD9A9-FE09-4A1E-A1D7-520D00531306",
+                "8A4F5770-50AA-4ED3-874A-99B710DB6FEA",
+                "B325BFBE-C7BE-4AB8-8357-139E652D2F6B",
+                "31741CC4-1A2A-4111-A581-E00B447D2D06",
+                "C80187A5-73A3-491A-901A-017C3FA953E9",
+                "3F324816-667B-46AE-86EE-9B0C0C6C11B4",
+            ],
+            Architecture::TileGx => [
+                "C50CDD70-3862-4CC3-90E1-809A8C93EE2C",
+                "55497029-C7C1-44CC-AA39-815ED1558630",
+                "966061EC-28E4-4B2E-B4A5-1F0A825A1D84",
+                "2FB4BF56-07FA-42DA-8132-6B139F2026AE",
+                "B3671439-97B0-4A53-90F7-2D5A8F3AD47B",
+                "4EDE75E2-6CCC-4CC8-B9C7-70334B087510",
+            ],
+        }
+    }
+}
+
+/// Partition type defined by the Discoverable Partitions Specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DpsType {
+    /// Root file system
+    Root(Architecture),
+    /// /usr file system
+    Usr(Architecture),
+    /// dm-verity hash data for root
+    RootVerity(Architecture),
+    /// dm-verity hash data for /usr
+    UsrVerity(Architecture),
+    /// dm-verity signature for root
+    RootVeritySig(Architecture),
+    /// dm-verity signature for /usr
+    UsrVeritySig(Architecture),
+    /// EFI System Partition
+    Esp,
+    /// Extended Boot Loader Partition
+    Xbootldr,
+    /// Swap
+    Swap,
+    /// /home file system
+    Home,
+    /// /srv file system
+    Srv,
+    /// /var file system
+    Var,
+    /// /var/tmp file system
+    Tmp,
+}
+
+impl DpsType {
+    /// Return type GUID, uppercase as used by libfdisk
+    pub fn guid(&self) -> &'static str {
+        match *self {
+            DpsType::Root(arch) => arch.guids()[0],
+            DpsType::Usr(arch) => arch.guids()[1],
+            DpsType::RootVerity(arch) => arch.guids()[2],
+            DpsType::UsrVerity(arch) => arch.guids()[3],
+            DpsType::RootVeritySig(arch) => arch.guids()[4],
+            DpsType::UsrVeritySig(arch) => arch.guids()[5],
+            DpsType::Esp => "C12A7328-F81F-11D2-BA4B-00A0C93EC93B",
+            DpsType::Xbootldr => "BC13C2FF-59E6-4262-A352-B275FD6F7172",
+            DpsType::Swap => "0657FD6D-A4AB-43C4-84E5-0933C84B4F4F",
+            DpsType::Home => "933AC7E1-2EB4-4F13-B844-0E14E2AEF915",
+            DpsType::Srv => "3B8F8425-20E0-4F3B-907F-1A25A76F98E8",
+            DpsType::Var => "4D21B016-B534-45C2-A9FB-5C16E091FD2D",
+            DpsType::Tmp => "7EC6F557-3BC5-4ACA-B293-16EF5DF639D1",
+        }
+    }
+
+    /// Search for the type by GUID, the comparison is case insensitive
+    pub fn from_guid(guid: &str) -> Option<DpsType> {
+        let generic = [
+            DpsType::Esp,
+            DpsType::Xbootldr,
+            DpsType::Swap,
+            DpsType::Home,
+            DpsType::Srv,
+            DpsType::Var,
+            DpsType::Tmp,
+        ];
+        let specific = Architecture::ALL.iter().flat_map(|&arch| {
+            [
+                DpsType::Root(arch),
+                DpsType::Usr(arch),
+                DpsType::RootVerity(arch),
+                DpsType::UsrVerity(arch),
+                DpsType::RootVeritySig(arch),
+                DpsType::UsrVeritySig(arch),
+            ]
+        });
+        generic
+            .iter()
+            .copied()
+            .chain(specific)
+            .find(|t| t.guid().eq_ignore_ascii_case(guid))
+    }
+}
+
+impl Label<'_> {
+    /// Return GPT partition type for the DPS type
+    pub fn dps_parttype(&self, t: DpsType) -> Result<Option<PartType>> {
+        self.parttype_from_string(t.guid())
+    }
+}
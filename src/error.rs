@@ -0,0 +1,130 @@
+//! Errors returned by the wrappers.
+
+use nix::errno::Errno;
+use std::ffi::NulError;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::Utf8Error;
+
+/// Result type used by all wrappers
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned by the wrappers
+#[derive(Debug)]
+pub enum Error {
+    /// libfdisk function failed
+    Fdisk {
+        /// Failed operation, e.g. "writing disk label"
+        op: &'static str,
+        /// Decoded error code
+        errno: Errno,
+        /// Assigned device, if known
+        device: Option<PathBuf>,
+    },
+    /// libfdisk has no value for the requested item
+    NotFound(&'static str),
+    /// Script header has unexpected value
+    InvalidHeader { name: String, value: String },
+    /// Argument is out of range allowed by the label
+    InvalidArgument { name: &'static str, value: String },
+    /// Argument contains an interior nul byte
+    Nul(NulError),
+    /// String returned by libfdisk is not valid UTF-8
+    Utf8(Utf8Error),
+    /// JSON dump can't be parsed
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+}
+
+impl Error {
+    /// Builds error from libfdisk return code. The library returns negative
+    /// errno, but some functions return it as a positive number.
+    pub(crate) fn fdisk(op: &'static str, rc: i32) -> Error {
+        Error::Fdisk {
+            op,
+            errno: Errno::from_i32(rc.abs()),
+            device: None,
+        }
+    }
+
+    /// Builds error from the current errno, used for functions returning NULL.
+    pub(crate) fn last(op: &'static str) -> Error {
+        Error::Fdisk {
+            op,
+            errno: Errno::last(),
+            device: None,
+        }
+    }
+
+    /// Attaches device path to the error
+    pub(crate) fn with_device(self, device: Option<PathBuf>) -> Error {
+        match self {
+            Error::Fdisk { op, errno, .. } => Error::Fdisk { op, errno, device },
+            e => e,
+        }
+    }
+
+    /// Return errno if the error comes from libfdisk
+    pub fn errno(&self) -> Option<Errno> {
+        match self {
+            Error::Fdisk { errno, .. } => Some(*errno),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Fdisk {
+                op,
+                errno,
+                device: Some(device),
+            } => write!(f, "{} on {}: {}", op, device.display(), errno),
+            Error::Fdisk { op, errno, .. } => write!(f, "{}: {}", op, errno),
+            Error::NotFound(what) => write!(f, "no valid {}", what),
+            Error::InvalidHeader { name, value } => {
+                write!(f, "invalid script header {}: '{}'", name, value)
+            }
+            Error::InvalidArgument { name, value } => write!(f, "invalid {}: '{}'", name, value),
+            Error::Nul(e) => write!(f, "{}", e),
+            Error::Utf8(e) => write!(f, "{}", e),
+            #[cfg(feature = "json")]
+            Error::Json(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Fdisk { errno, .. } => Some(errno),
+            Error::Nul(e) => Some(e),
+            Error::Utf8(e) => Some(e),
+            #[cfg(feature = "json")]
+            Error::Json(e) => Some(e),
+            Error::NotFound(_) | Error::InvalidHeader { .. } | Error::InvalidArgument { .. } => {
+                None
+            }
+        }
+    }
+}
+
+impl From<NulError> for Error {
+    fn from(e: NulError) -> Error {
+        Error::Nul(e)
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(e: Utf8Error) -> Error {
+        Error::Utf8(e)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
@@ -0,0 +1,111 @@
+//! Free space — unpartitioned areas on the device usable for new partitions.
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::table::Table;
+use fdisk_sys;
+
+/// Unpartitioned area, the values are in sectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpace {
+    /// First sector
+    pub start: u64,
+    /// Number of sectors
+    pub size: u64,
+    /// Logical sector size in bytes
+    pub sector_size: u64,
+}
+
+impl FreeSpace {
+    /// Return last sector, None if the area is empty
+    pub fn end(&self) -> Option<u64> {
+        self.size
+            .checked_sub(1)
+            .and_then(|n| self.start.checked_add(n))
+    }
+
+    /// Return offset of the first sector in bytes
+    pub fn start_bytes(&self) -> u64 {
+        self.start * self.sector_size
+    }
+
+    /// Return size in bytes
+    pub fn size_bytes(&self) -> u64 {
+        self.size * self.sector_size
+    }
+}
+
+impl Context {
+    /// Return table with free spaces, the entries are marked by
+    /// Partition::is_freespace(). The areas are aligned to grain.
+    pub fn free_spaces(&self) -> Result<Table> {
+        let mut table = Table::new();
+        match unsafe { fdisk_sys::fdisk_get_freespaces(self.ptr, &mut table.ptr) } {
+            0 => Ok(table),
+            v => Err(self.error("getting free spaces", v)),
+        }
+    }
+
+    /// Return free spaces in disk order
+    pub fn free_space_list(&self) -> Result<Vec<FreeSpace>> {
+        let sector_size = self.sector_size();
+        let table = self.free_spaces()?;
+        let mut list = Vec::with_capacity(table.nents());
+        for pa in &table {
+            let (start, size) = match (pa.start(), pa.size()) {
+                (Some(start), Some(size)) => (start, size),
+                _ => return Err(Error::NotFound("free space range")),
+            };
+            if size > 0 {
+                list.push(FreeSpace {
+                    start,
+                    size,
+                    sector_size,
+                });
+            }
+        }
+        list.sort_by_key(|fs| fs.start);
+        Ok(list)
+    }
+
+    /// Return the largest free space, None if the device is full
+    pub fn largest_free_space(&self) -> Result<Option<FreeSpace>> {
+        let list = self.free_space_list()?;
+        Ok(list.into_iter().rev().max_by_key(|fs| fs.size))
+    }
+
+    /// Return free spaces with at least `bytes` bytes
+    /// # Arguments
+    /// * `bytes` - minimal size in bytes
+    pub fn free_spaces_at_least(&self, bytes: u64) -> Result<Vec<FreeSpace>> {
+        let mut list = self.free_space_list()?;
+        list.retain(|fs| fs.size_bytes() >= bytes);
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FreeSpace;
+
+    #[test]
+    fn free_space_end() {
+        let fs = FreeSpace {
+            start: 2048,
+            size: 4096,
+            sector_size: 512,
+        };
+        assert_eq!(fs.end(), Some(6143));
+        assert_eq!(FreeSpace { size: 1, ..fs }.end(), Some(2048));
+        assert_eq!(FreeSpace { size: 0, ..fs }.end(), None);
+        assert_eq!(
+            FreeSpace {
+                start: 0,
+                size: 0,
+                ..fs
+            }
+            .end(),
+            None
+        );
+    }
+}
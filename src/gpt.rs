@@ -0,0 +1,261 @@
+//! GPT — label specific functions for GUID Partition Table.
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::label::{DiskLabel, Label};
+use crate::labelitem::LabelItem;
+use crate::partition::Partition;
+use bitflags::bitflags;
+use fdisk_sys;
+
+/// DOS partition type of the protective partition
+const PMBR_TYPE: u32 = 0xee;
+
+/// Size of GPT partition entry in bytes
+const GPT_ENTRY_SIZE: u64 = 128;
+
+bitflags! {
+    /// GPT partition entry attributes. The bits 48-63 are defined by
+    /// the partition type, the reserved bits are retained as they are.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct GptAttributes: u64 {
+        /// The partition is required for the platform to function
+        const REQUIRED_PARTITION = 1 << 0;
+        /// EFI firmware should not produce EFI_BLOCK_IO_PROTOCOL for the partition
+        const NO_BLOCK_IO_PROTOCOL = 1 << 1;
+        /// The partition may be bootable by legacy BIOS firmware
+        const LEGACY_BIOS_BOOTABLE = 1 << 2;
+        /// Type specific bit 48
+        const TYPE_SPECIFIC_48 = 1 << 48;
+        /// Type specific bit 49
+        const TYPE_SPECIFIC_49 = 1 << 49;
+        /// Type specific bit 50
+        const TYPE_SPECIFIC_50 = 1 << 50;
+        /// Type specific bit 51
+        const TYPE_SPECIFIC_51 = 1 << 51;
+        /// Type specific bit 52
+        const TYPE_SPECIFIC_52 = 1 << 52;
+        /// Type specific bit 53
+        const TYPE_SPECIFIC_53 = 1 << 53;
+        /// Type specific bit 54
+        const TYPE_SPECIFIC_54 = 1 << 54;
+        /// Type specific bit 55
+        const TYPE_SPECIFIC_55 = 1 << 55;
+        /// Type specific bit 56
+        const TYPE_SPECIFIC_56 = 1 << 56;
+        /// Type specific bit 57
+        const TYPE_SPECIFIC_57 = 1 << 57;
+        /// Type specific bit 58
+        const TYPE_SPECIFIC_58 = 1 << 58;
+        /// Type specific bit 59
+        const TYPE_SPECIFIC_59 = 1 << 59;
+        /// Type specific bit 60
+        const TYPE_SPECIFIC_60 = 1 << 60;
+        /// Type specific bit 61
+        const TYPE_SPECIFIC_61 = 1 << 61;
+        /// Type specific bit 62
+        const TYPE_SPECIFIC_62 = 1 << 62;
+        /// Type specific bit 63
+        const TYPE_SPECIFIC_63 = 1 << 63;
+        /// All type specific bits
+        const TYPE_SPECIFIC = 0xffff << 48;
+
+        const _ = !0;
+    }
+}
+
+impl GptAttributes {
+    /// Return type specific bits 48-63 shifted to the lowest bits
+    pub fn type_specific(&self) -> u16 {
+        (self.bits() >> 48) as u16
+    }
+
+    /// Replaces type specific bits 48-63 by `bits`
+    pub fn set_type_specific(&mut self, bits: u16) {
+        *self = GptAttributes::from_bits_retain(
+            (self.bits() & !GptAttributes::TYPE_SPECIFIC.bits()) | (u64::from(bits) << 48),
+        );
+    }
+}
+
+impl Context {
+    /// Return attributes of the GPT partition
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    pub fn gpt_partition_attrs(&self, partno: usize) -> Result<GptAttributes> {
+        let mut attrs: u64 = 0;
+        match unsafe { fdisk_sys::fdisk_gpt_get_partition_attrs(self.ptr, partno, &mut attrs) } {
+            0 => Ok(GptAttributes::from_bits_retain(attrs)),
+            v => Err(self.error("getting GPT partition attributes", v)),
+        }
+    }
+
+    /// Sets attributes of the GPT partition, the previous attributes are replaced.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    /// * `attrs` - new attributes
+    pub fn set_gpt_partition_attrs(&self, partno: usize, attrs: GptAttributes) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_gpt_set_partition_attrs(self.ptr, partno, attrs.bits()) } {
+            0 => Ok(()),
+            v => Err(self.error("setting GPT partition attributes", v)),
+        }
+    }
+
+    /// Sets or clears the attributes of the GPT partition, other bits are kept.
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    /// * `attrs` - attributes to change
+    /// * `enable` - set or clear the attributes
+    pub fn enable_gpt_partition_attrs(
+        &self,
+        partno: usize,
+        attrs: GptAttributes,
+        enable: bool,
+    ) -> Result<()> {
+        let mut current = self.gpt_partition_attrs(partno)?;
+        current.set(attrs, enable);
+        self.set_gpt_partition_attrs(partno, current)
+    }
+
+    /// Resizes GPT partition entries array, return the new first usable LBA.
+    /// Fails if the array would overlap the first partition or if the array
+    /// is too small for the used partition entries.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `n` - number of partition entries
+    pub fn gpt_set_npartitions(&self, n: u32) -> Result<u64> {
+        let invalid = || Error::InvalidArgument {
+            name: "number of GPT partition entries",
+            value: n.to_string(),
+        };
+        let entries_lba = self.gpt_header_lba(|item| match item {
+            LabelItem::GptEntriesLba(lba) => Some(lba),
+            _ => None,
+        })?;
+        let sector_size = self.sector_size();
+        let first_lba = entries_lba + (u64::from(n) * GPT_ENTRY_SIZE).div_ceil(sector_size);
+        for pa in &self.get_partitions()? {
+            if pa.partno().is_some_and(|partno| partno >= n as usize) {
+                return Err(invalid());
+            }
+            if pa.start().is_some_and(|start| start < first_lba) {
+                return Err(invalid());
+            }
+        }
+        match unsafe { fdisk_sys::fdisk_gpt_set_npartitions(self.ptr, n) } {
+            0 => self.gpt_header_lba(|item| match item {
+                LabelItem::GptFirstLba(lba) => Some(lba),
+                _ => None,
+            }),
+            v => Err(self.error("setting number of GPT partition entries", v)),
+        }
+    }
+
+    /// Return GPT header field selected by `f` from label items
+    fn gpt_header_lba<F: FnMut(LabelItem) -> Option<u64>>(&self, f: F) -> Result<u64> {
+        self.label_items()?
+            .into_iter()
+            .find_map(f)
+            .ok_or(Error::NotFound("GPT header field"))
+    }
+
+    /// Enables or disables minimized GPT layout, the last usable LBA and backup
+    /// header with partition entries are placed right after the last partition
+    /// by write_disklabel(). The rest of the device may be truncated.
+    pub fn gpt_enable_minimize(&self, enable: bool) -> Result<()> {
+        let lb = self.gpt_label()?;
+        unsafe { fdisk_sys::fdisk_gpt_enable_minimize(lb.ptr, enable as i32) };
+        Ok(())
+    }
+
+    /// Moves GPT backup header and partition entries to the end of the device and
+    /// writes the disklabel, the same as `sfdisk --relocate gpt-bak-std`. The last
+    /// usable LBA is updated. The disklabel is probed again, so the in-memory
+    /// changes are discarded. Return false if the backup header is already at the end.
+    pub fn gpt_relocate_backup(&self) -> Result<bool> {
+        // probe on-disk header, libfdisk relocates the header in memory by default
+        self.gpt_disable_relocation(true)?;
+        let res = self
+            .reassign_device()
+            .and_then(|_| self.gpt_backup_misplaced());
+        self.gpt_disable_relocation(false)?;
+        if !res? {
+            return Ok(false);
+        }
+        self.reassign_device()?;
+        let lb = self.gpt_label()?;
+        unsafe { fdisk_sys::fdisk_label_set_changed(lb.ptr, 1) };
+        self.write_disklabel()?;
+        Ok(true)
+    }
+
+    /// Return true if the last usable LBA is not right before backup
+    /// header and partition entries at the end of the device
+    fn gpt_backup_misplaced(&self) -> Result<bool> {
+        if !self.is_labeltype(DiskLabel::Gpt) {
+            return Err(Error::NotFound("GPT label"));
+        }
+        let entries = self.gpt_header_lba(|item| match item {
+            LabelItem::GptEntriesAlloc(n) => Some(n),
+            _ => None,
+        })?;
+        let backup = 1 + (entries * GPT_ENTRY_SIZE).div_ceil(self.sector_size());
+        Ok(self.last_lba() < self.logical_sectors().saturating_sub(backup + 1))
+    }
+
+    /// Disables relocation of GPT backup header by probing, the in-memory
+    /// header describes the on-disk layout. Use before assign_device().
+    pub fn gpt_disable_relocation(&self, disable: bool) -> Result<()> {
+        let lb = self.gpt_label()?;
+        unsafe { fdisk_sys::fdisk_gpt_disable_relocation(lb.ptr, disable as i32) };
+        Ok(())
+    }
+
+    /// Return true if the protective MBR contains other partitions than
+    /// the protective one (hybrid MBR)
+    pub fn gpt_is_hybrid(&self) -> bool {
+        matches!(unsafe { fdisk_sys::fdisk_gpt_is_hybrid(self.ptr) }, 1)
+    }
+
+    /// Return nested DOS context with the protective MBR of the GPT disk.
+    /// The changes are written by write_disklabel() of the nested context.
+    pub fn protective_mbr(&self) -> Result<Context> {
+        if !self.is_labeltype(DiskLabel::Gpt) {
+            return Err(Error::NotFound("GPT label"));
+        }
+        self.new_nested("dos")
+    }
+
+    /// Return partitions of hybrid MBR, the protective partition is not included
+    pub fn hybrid_partitions(&self) -> Result<Vec<Partition>> {
+        let pmbr = self.protective_mbr()?;
+        let table = pmbr.get_partitions()?;
+        Ok(table
+            .iter()
+            .filter(|pa| pa.part_type().map(|t| t.code()) != Some(PMBR_TYPE))
+            .map(|pa| pa.to_partition())
+            .collect())
+    }
+
+    /// Toggles boot flag of the protective partition in the protective MBR
+    /// and writes the protective MBR to the device. Return the new state of the flag.
+    pub fn toggle_pmbr_bootable(&self) -> Result<bool> {
+        let pmbr = self.protective_mbr()?;
+        let partno = pmbr
+            .get_partitions()?
+            .iter()
+            .find(|pa| pa.part_type().map(|t| t.code()) == Some(PMBR_TYPE))
+            .and_then(|pa| pa.partno())
+            .ok_or(Error::NotFound("protective partition"))?;
+        pmbr.toggle_partition_flag(partno, u64::from(fdisk_sys::DOS_FLAG_ACTIVE))?;
+        pmbr.write_disklabel()?;
+        Ok(pmbr.partition(partno)?.is_bootable())
+    }
+
+    /// Return GPT label driver
+    fn gpt_label(&self) -> Result<Label<'_>> {
+        self.label_by_type(DiskLabel::Gpt)
+            .ok_or(Error::NotFound("GPT label"))
+    }
+}
@@ -2,18 +2,18 @@
 //! The iterator keeps the direction and the last position for access
 //! to the internal library tables/lists.
 //!
-use crate::partition::Partition;
+use crate::partition::PartitionRef;
 use crate::table::Table;
 use fdisk_sys;
 
 /// Unified iterator
 pub struct Iter<'a> {
-    tbl: &'a mut Table,
+    tbl: &'a Table,
     ptr: *mut fdisk_sys::fdisk_iter,
 }
 
 impl<'a> Iter<'a> {
-    pub fn new(tbl: &mut Table) -> Iter {
+    pub fn new(tbl: &Table) -> Iter<'_> {
         Iter {
             tbl,
             ptr: unsafe { fdisk_sys::fdisk_new_iter(fdisk_sys::FDISK_ITER_FORWARD as i32) },
@@ -22,12 +22,12 @@ impl<'a> Iter<'a> {
 }
 
 impl<'a> Iterator for Iter<'a> {
-    type Item = Partition;
+    type Item = PartitionRef<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut ptr: *mut fdisk_sys::fdisk_partition = std::ptr::null_mut();
         match unsafe { fdisk_sys::fdisk_table_next_partition(self.tbl.ptr, self.ptr, &mut ptr) } {
-            0 => Some(Partition { ptr }),
+            0 => Some(unsafe { PartitionRef::from_ptr(ptr) }),
             1 => None,
             _ => panic!("bad value"),
         }
@@ -0,0 +1,63 @@
+//! Typed representation of the JSON dumps, the same as `sfdisk --json` prints.
+
+use crate::context::Context;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+
+/// Top level object of the JSON dump
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dump {
+    pub partitiontable: PartitionTable,
+}
+
+/// Disklabel headers and partitions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionTable {
+    pub label: String,
+    /// Disk identifier (GUID for GPT, hex signature for DOS)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub firstlba: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastlba: Option<u64>,
+    #[serde(rename = "table-length", skip_serializing_if = "Option::is_none")]
+    pub table_length: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sectorsize: Option<u64>,
+    #[serde(default)]
+    pub partitions: Vec<PartitionEntry>,
+}
+
+/// Partition entry, start and size are in sectors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionEntry {
+    pub node: String,
+    pub start: u64,
+    pub size: u64,
+    /// Type code (DOS) or type GUID (GPT)
+    #[serde(rename = "type")]
+    pub part_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attrs: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bootable: bool,
+}
+
+impl Context {
+    /// Return the in-memory disklabel parsed from the JSON dump
+    pub fn partition_table(&self) -> Result<PartitionTable> {
+        let dump: Dump = serde_json::from_str(&self.dump_json()?)?;
+        Ok(dump.partitiontable)
+    }
+}
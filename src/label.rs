@@ -1,15 +1,22 @@
 //! Label — disk label (PT) specific data and functions
 
 use crate::context::Context;
-use anyhow::{anyhow, Result};
+use crate::error::{Error, Result};
 use fdisk_sys;
 use std::ffi::{CStr, CString};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+use uuid::Uuid;
 
-/// Container for fdisk partitions
-pub struct Label {
+/// Container for fdisk partitions, the label driver is owned by the context
+pub struct Label<'a> {
     pub(crate) ptr: *mut fdisk_sys::fdisk_label,
+    _cxt: PhantomData<&'a Context>,
 }
+
 #[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DiskLabel {
     Dos = fdisk_sys::fdisk_labeltype_FDISK_DISKLABEL_DOS,
     Sun = fdisk_sys::fdisk_labeltype_FDISK_DISKLABEL_SUN,
@@ -30,23 +37,67 @@ impl AsRef<str> for DiskLabel {
     }
 }
 
-impl ToString for DiskLabel {
-    fn to_string(&self) -> String {
-        self.as_ref().to_string()
+impl DiskLabel {
+    /// Return label type for FDISK_DISKLABEL_* value
+    fn from_raw(id: u32) -> Option<DiskLabel> {
+        match id {
+            fdisk_sys::fdisk_labeltype_FDISK_DISKLABEL_DOS => Some(DiskLabel::Dos),
+            fdisk_sys::fdisk_labeltype_FDISK_DISKLABEL_SUN => Some(DiskLabel::Sun),
+            fdisk_sys::fdisk_labeltype_FDISK_DISKLABEL_SGI => Some(DiskLabel::Sgi),
+            fdisk_sys::fdisk_labeltype_FDISK_DISKLABEL_BSD => Some(DiskLabel::Bsd),
+            fdisk_sys::fdisk_labeltype_FDISK_DISKLABEL_GPT => Some(DiskLabel::Gpt),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DiskLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// Disk identifier, typed by label
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiskId {
+    /// GPT disk GUID
+    Gpt(Uuid),
+    /// DOS (MBR) disk signature
+    Dos(u32),
+    /// Identifier of other labels as returned by libfdisk
+    Other(String),
+}
+
+impl fmt::Display for DiskId {
+    /// Formats the identifier the same way as libfdisk does
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiskId::Gpt(uuid) => write!(f, "{:X}", uuid),
+            DiskId::Dos(id) => write!(f, "0x{:08x}", id),
+            DiskId::Other(id) => write!(f, "{}", id),
+        }
     }
 }
 
-impl Label {
+impl<'a> Label<'a> {
+    /// Return label driver for the pointer owned by the context, None for NULL
+    pub(crate) fn from_ptr(ptr: *mut fdisk_sys::fdisk_label) -> Option<Label<'a>> {
+        if ptr.is_null() {
+            return None;
+        }
+        Some(Label {
+            ptr,
+            _cxt: PhantomData,
+        })
+    }
+
     pub fn get_name(&self) -> Result<String> {
         unsafe {
             let src = fdisk_sys::fdisk_label_get_name(self.ptr);
             if src.is_null() {
-                return Err(anyhow!("no valid name"));
-            }
-            match CStr::from_ptr(src).to_str() {
-                Ok(v) => Ok(v.to_string()),
-                Err(e) => Err(e.into()),
+                return Err(Error::NotFound("label name"));
             }
+            Ok(CStr::from_ptr(src).to_str()?.to_string())
         }
     }
 
@@ -59,6 +110,89 @@ impl Label {
     pub fn is_changed(&self) -> bool {
         unsafe { fdisk_sys::fdisk_label_is_changed(self.ptr) == 1 }
     }
+
+    /// Return label type, None for types unknown to this crate
+    pub fn label_type(&self) -> Option<DiskLabel> {
+        DiskLabel::from_raw(unsafe { fdisk_sys::fdisk_label_get_type(self.ptr) } as u32)
+    }
+
+    /// Disables or enables the label driver, the disabled drivers
+    /// are ignored when probing the device.
+    /// # Arguments
+    /// * `disabled` - true to disable the driver
+    pub fn set_disabled(&self, disabled: bool) {
+        unsafe { fdisk_sys::fdisk_label_set_disabled(self.ptr, disabled as i32) }
+    }
+
+    /// Returns `true` if the label requires CHS geometry
+    pub fn require_geometry(&self) -> bool {
+        unsafe { fdisk_sys::fdisk_label_require_geometry(self.ptr) == 1 }
+    }
+
+    /// Returns `true` if the partition types are numbers (e.g. DOS 0x83)
+    pub fn has_code_parttypes(&self) -> bool {
+        unsafe { fdisk_sys::fdisk_label_has_code_parttypes(self.ptr) == 1 }
+    }
+
+    /// Returns `true` if the label supports partition type shortcuts (e.g. "L")
+    pub fn has_parttypes_shortcuts(&self) -> bool {
+        unsafe { fdisk_sys::fdisk_label_has_parttypes_shortcuts(self.ptr) == 1 }
+    }
+
+    /// Return range of cylinders allowed by the label
+    pub fn geomrange_cylinders(&self) -> Result<RangeInclusive<u64>> {
+        let (mut min, mut max) = (0, 0);
+        match unsafe {
+            fdisk_sys::fdisk_label_get_geomrange_cylinders(self.ptr, &mut min, &mut max)
+        } {
+            0 => Ok(min..=max),
+            v => Err(Error::fdisk("getting cylinders range", v)),
+        }
+    }
+
+    /// Return range of heads allowed by the label
+    pub fn geomrange_heads(&self) -> Result<RangeInclusive<u32>> {
+        let (mut min, mut max) = (0, 0);
+        match unsafe { fdisk_sys::fdisk_label_get_geomrange_heads(self.ptr, &mut min, &mut max) } {
+            0 => Ok(min..=max),
+            v => Err(Error::fdisk("getting heads range", v)),
+        }
+    }
+
+    /// Return range of sectors per track allowed by the label
+    pub fn geomrange_sectors(&self) -> Result<RangeInclusive<u64>> {
+        let (mut min, mut max) = (0, 0);
+        match unsafe { fdisk_sys::fdisk_label_get_geomrange_sectors(self.ptr, &mut min, &mut max) }
+        {
+            0 => Ok(min..=max),
+            v => Err(Error::fdisk("getting sectors range", v)),
+        }
+    }
+}
+
+/// Iterator over label drivers of the context
+pub struct Labels<'a> {
+    cxt: &'a Context,
+    ptr: *mut fdisk_sys::fdisk_label,
+    done: bool,
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = Label<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // libfdisk starts again from the first driver after the last one
+        if self.done {
+            return None;
+        }
+        match unsafe { fdisk_sys::fdisk_next_label(self.cxt.ptr, &mut self.ptr) } {
+            0 if !self.ptr.is_null() => Label::from_ptr(self.ptr),
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
 }
 
 impl Context {
@@ -79,11 +213,7 @@ impl Context {
         let label = CString::new(name.as_bytes())?;
         match unsafe { fdisk_sys::fdisk_create_disklabel(self.ptr, label.as_ptr()) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "creating disk label '{}', errno: {}",
-                name,
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("creating disk label", v)),
         }
     }
 
@@ -91,36 +221,103 @@ impl Context {
     pub fn write_disklabel(&self) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_write_disklabel(self.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "writting disk label, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v => Err(self.error("writing disk label", v)),
         }
     }
 
-    /// Verifies the partition table.
-    pub fn verify_disklabel(&self) -> Result<()> {
+    /// Verifies the partition table, return number of detected problems.
+    /// The problems are described by warnings, see enable_diagnostics().
+    pub fn verify_disklabel(&self) -> Result<usize> {
         match unsafe { fdisk_sys::fdisk_verify_disklabel(self.ptr) } {
-            0 => Ok(()),
-            v => Err(anyhow!(
-                "verifying disk label, errno: {}",
-                nix::errno::from_i32(-v)
-            )),
+            v if v >= 0 => Ok(v as usize),
+            v => Err(self.error("verifying disk label", v)),
+        }
+    }
+
+    /// Return disk identifier of the current label, None if the label has no identifier
+    pub fn disklabel_id(&self) -> Result<Option<DiskId>> {
+        let mut id: *mut libc::c_char = std::ptr::null_mut();
+        let rc = unsafe { fdisk_sys::fdisk_get_disklabel_id(self.ptr, &mut id) };
+        if rc != 0 {
+            return Err(self.error("getting disk identifier", rc));
+        }
+        if id.is_null() {
+            return Ok(None);
         }
+        let value = unsafe { CStr::from_ptr(id) }
+            .to_str()
+            .map(|s| s.to_string());
+        unsafe { libc::free(id as *mut libc::c_void) };
+        Ok(Some(self.parse_disklabel_id(value?)?))
     }
 
-    /// If no name specified then returns the current context label.
-    pub fn get_label<L: AsRef<str>>(&self, name: L) -> Result<Label> {
-        let name = match name.as_ref().is_empty() {
-            false => CString::new(name.as_ref().as_bytes())?.as_ptr(),
-            true => std::ptr::null(),
+    /// Converts disk identifier in libfdisk format to the type of the current label
+    pub(crate) fn parse_disklabel_id(&self, value: String) -> Result<DiskId> {
+        let invalid = || Error::InvalidArgument {
+            name: "disk identifier",
+            value: value.clone(),
         };
-        unsafe {
-            let ptr = fdisk_sys::fdisk_get_label(self.ptr, name);
-            if ptr.is_null() {
-                return Err(anyhow!("{}", "no valid label"));
-            }
-            Ok(Label { ptr })
+        if self.is_labeltype(DiskLabel::Gpt) {
+            Ok(DiskId::Gpt(Uuid::parse_str(&value).map_err(|_| invalid())?))
+        } else if self.is_labeltype(DiskLabel::Dos) {
+            let hex = value.trim_start_matches("0x");
+            Ok(DiskId::Dos(
+                u32::from_str_radix(hex, 16).map_err(|_| invalid())?,
+            ))
+        } else {
+            Ok(DiskId::Other(value))
+        }
+    }
+
+    /// Sets disk identifier of the current label.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `id` - new identifier, the variant has to match the label
+    pub fn set_disklabel_id(&self, id: &DiskId) -> Result<()> {
+        self.set_disklabel_id_from_string(&id.to_string())
+    }
+
+    /// Sets disk identifier of the current label from string, e.g. GUID
+    /// for GPT or hex number ("0x12345678") for DOS.
+    /// # Arguments
+    /// * `id` - new identifier
+    pub fn set_disklabel_id_from_string(&self, id: &str) -> Result<()> {
+        let id = CString::new(id.as_bytes())?;
+        match unsafe { fdisk_sys::fdisk_set_disklabel_id_from_string(self.ptr, id.as_ptr()) } {
+            0 => Ok(()),
+            v => Err(self.error("setting disk identifier", v)),
+        }
+    }
+
+    /// Return label driver by name (e.g. "gpt" or DiskLabel::Gpt), None if there is
+    /// no such driver. If no name specified then returns the current context label.
+    pub fn get_label<L: AsRef<str>>(&self, name: L) -> Result<Option<Label<'_>>> {
+        let name = name.as_ref();
+        if name.is_empty() {
+            return Ok(self.label());
+        }
+        let name = CString::new(name.as_bytes())?;
+        Ok(Label::from_ptr(unsafe {
+            fdisk_sys::fdisk_get_label(self.ptr, name.as_ptr())
+        }))
+    }
+
+    /// Return the current label driver, None if there is no label
+    pub fn label(&self) -> Option<Label<'_>> {
+        Label::from_ptr(unsafe { fdisk_sys::fdisk_get_label(self.ptr, std::ptr::null()) })
+    }
+
+    /// Return label driver of the type, None if the driver is not available
+    pub fn label_by_type(&self, id: DiskLabel) -> Option<Label<'_>> {
+        self.labels().find(|lb| lb.label_type() == Some(id))
+    }
+
+    /// Return iterator over all label drivers, including the disabled ones
+    pub fn labels(&self) -> Labels<'_> {
+        Labels {
+            cxt: self,
+            ptr: std::ptr::null_mut(),
+            done: false,
         }
     }
 
@@ -0,0 +1,278 @@
+//! Label item — label specific metadata, e.g. GPT header fields or SUN geometry.
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::label::{DiskId, DiskLabel};
+use fdisk_sys;
+use std::ffi::CStr;
+
+/// Raw value of label item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelItemValue {
+    Number(u64),
+    String(String),
+}
+
+/// Label item, one variant for each FDISK_LABELITEM_* and *_LABELITEM_*
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelItem {
+    /// Disk identifier, FDISK_LABELITEM_ID
+    Id(DiskId),
+    /// GPT first usable LBA
+    GptFirstLba(u64),
+    /// GPT last usable LBA
+    GptLastLba(u64),
+    /// GPT LBA of the other (alternative) header
+    GptAltLba(u64),
+    /// GPT first LBA of partition entries array
+    GptEntriesLba(u64),
+    /// GPT number of allocated partition entries
+    GptEntriesAlloc(u64),
+    /// SUN label identifier (ASCII label)
+    SunLabelId(String),
+    /// SUN volume identifier
+    SunVtocId(String),
+    /// SUN disk rotational speed
+    SunRpm(u64),
+    /// SUN number of alternate cylinders
+    SunAcyl(u64),
+    /// SUN number of physical cylinders
+    SunPcyl(u64),
+    /// SUN extra sectors per cylinder
+    SunApc(u64),
+    /// SUN interleave factor
+    SunIntrlv(u64),
+    /// BSD drive type
+    BsdType(String),
+    /// BSD disk type name
+    BsdDisk(String),
+    /// BSD pack identifier
+    BsdPackname(String),
+    /// BSD flags
+    BsdFlags(String),
+    /// BSD bytes per sector
+    BsdSecsize(u64),
+    /// BSD tracks per cylinder
+    BsdNtracks(u64),
+    /// BSD sectors per cylinder
+    BsdSecpercyl(u64),
+    /// BSD number of cylinders
+    BsdCylinders(u64),
+    /// BSD disk rotational speed
+    BsdRpm(u64),
+    /// BSD hardware sector interleave
+    BsdInterleave(u64),
+    /// BSD sector 0 skew per track
+    BsdTrackskew(u64),
+    /// BSD sector 0 skew per cylinder
+    BsdCylinderskew(u64),
+    /// BSD head switch time
+    BsdHeadswitch(u64),
+    /// BSD track-to-track seek time
+    BsdTrkseek(u64),
+    /// SGI number of physical cylinders
+    SgiPcylcount(u64),
+    /// SGI spare sectors per cylinder
+    SgiSparecyl(u64),
+    /// SGI interleave factor
+    SgiIlfact(u64),
+    /// SGI boot file name
+    SgiBootfile(String),
+    /// Item unknown to this crate or with unexpected type of value
+    Other {
+        id: i32,
+        name: String,
+        value: LabelItemValue,
+    },
+}
+
+/// Allocated label item, unreferenced on drop
+struct RawItem(*mut fdisk_sys::fdisk_labelitem);
+
+impl RawItem {
+    fn name(&self) -> String {
+        unsafe {
+            let src = fdisk_sys::fdisk_labelitem_get_name(self.0);
+            if src.is_null() {
+                return String::new();
+            }
+            CStr::from_ptr(src).to_string_lossy().into_owned()
+        }
+    }
+
+    fn value(&self) -> Result<LabelItemValue> {
+        if unsafe { fdisk_sys::fdisk_labelitem_is_string(self.0) } == 1 {
+            let mut data: *const libc::c_char = std::ptr::null();
+            match unsafe { fdisk_sys::fdisk_labelitem_get_data_string(self.0, &mut data) } {
+                0 if data.is_null() => Ok(LabelItemValue::String(String::new())),
+                0 => Ok(LabelItemValue::String(
+                    unsafe { CStr::from_ptr(data) }.to_str()?.to_string(),
+                )),
+                v => Err(Error::fdisk("getting label item", v)),
+            }
+        } else {
+            let mut data: u64 = 0;
+            match unsafe { fdisk_sys::fdisk_labelitem_get_data_u64(self.0, &mut data) } {
+                0 => Ok(LabelItemValue::Number(data)),
+                v => Err(Error::fdisk("getting label item", v)),
+            }
+        }
+    }
+}
+
+impl Drop for RawItem {
+    fn drop(&mut self) {
+        unsafe { fdisk_sys::fdisk_unref_labelitem(self.0) }
+    }
+}
+
+impl Context {
+    /// Return all items supported by the current label
+    pub fn label_items(&self) -> Result<Vec<LabelItem>> {
+        let ptr = unsafe { fdisk_sys::fdisk_new_labelitem() };
+        if ptr.is_null() {
+            return Err(Error::last("allocating label item"));
+        }
+        let item = RawItem(ptr);
+        let mut items = Vec::new();
+        for id in 0.. {
+            unsafe { fdisk_sys::fdisk_reset_labelitem(item.0) };
+            match unsafe { fdisk_sys::fdisk_get_disklabel_item(self.ptr, id, item.0) } {
+                // success
+                0 => items.push(self.label_item(id, item.name(), item.value()?)?),
+                // the item is not supported by the label
+                1 => continue,
+                // no more items
+                2 => break,
+                v => return Err(self.error("getting label item", v)),
+            }
+        }
+        Ok(items)
+    }
+
+    /// Converts the raw item to the variant for the current label
+    fn label_item(&self, id: i32, name: String, value: LabelItemValue) -> Result<LabelItem> {
+        use LabelItemValue as V;
+
+        let id = id as u32;
+        let item = if id == fdisk_sys::fdisk_labelitem_gen_FDISK_LABELITEM_ID {
+            match &value {
+                V::String(s) => Some(LabelItem::Id(self.parse_disklabel_id(s.clone())?)),
+                _ => None,
+            }
+        } else if self.is_labeltype(DiskLabel::Gpt) {
+            match (id, &value) {
+                (fdisk_sys::fdisk_labelitem_gpt_GPT_LABELITEM_FIRSTLBA, V::Number(n)) => {
+                    Some(LabelItem::GptFirstLba(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_gpt_GPT_LABELITEM_LASTLBA, V::Number(n)) => {
+                    Some(LabelItem::GptLastLba(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_gpt_GPT_LABELITEM_ALTLBA, V::Number(n)) => {
+                    Some(LabelItem::GptAltLba(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_gpt_GPT_LABELITEM_ENTRIESLBA, V::Number(n)) => {
+                    Some(LabelItem::GptEntriesLba(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_gpt_GPT_LABELITEM_ENTRIESALLOC, V::Number(n)) => {
+                    Some(LabelItem::GptEntriesAlloc(*n))
+                }
+                _ => None,
+            }
+        } else if self.is_labeltype(DiskLabel::Sun) {
+            match (id, &value) {
+                (fdisk_sys::fdisk_labelitem_sun_SUN_LABELITEM_LABELID, V::String(s)) => {
+                    Some(LabelItem::SunLabelId(s.clone()))
+                }
+                (fdisk_sys::fdisk_labelitem_sun_SUN_LABELITEM_VTOCID, V::String(s)) => {
+                    Some(LabelItem::SunVtocId(s.clone()))
+                }
+                (fdisk_sys::fdisk_labelitem_sun_SUN_LABELITEM_RPM, V::Number(n)) => {
+                    Some(LabelItem::SunRpm(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_sun_SUN_LABELITEM_ACYL, V::Number(n)) => {
+                    Some(LabelItem::SunAcyl(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_sun_SUN_LABELITEM_PCYL, V::Number(n)) => {
+                    Some(LabelItem::SunPcyl(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_sun_SUN_LABELITEM_APC, V::Number(n)) => {
+                    Some(LabelItem::SunApc(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_sun_SUN_LABELITEM_INTRLV, V::Number(n)) => {
+                    Some(LabelItem::SunIntrlv(*n))
+                }
+                _ => None,
+            }
+        } else if self.is_labeltype(DiskLabel::Bsd) {
+            match (id, &value) {
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_TYPE, V::String(s)) => {
+                    Some(LabelItem::BsdType(s.clone()))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_DISK, V::String(s)) => {
+                    Some(LabelItem::BsdDisk(s.clone()))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_PACKNAME, V::String(s)) => {
+                    Some(LabelItem::BsdPackname(s.clone()))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_FLAGS, V::String(s)) => {
+                    Some(LabelItem::BsdFlags(s.clone()))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_SECSIZE, V::Number(n)) => {
+                    Some(LabelItem::BsdSecsize(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_NTRACKS, V::Number(n)) => {
+                    Some(LabelItem::BsdNtracks(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_SECPERCYL, V::Number(n)) => {
+                    Some(LabelItem::BsdSecpercyl(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_CYLINDERS, V::Number(n)) => {
+                    Some(LabelItem::BsdCylinders(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_RPM, V::Number(n)) => {
+                    Some(LabelItem::BsdRpm(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_INTERLEAVE, V::Number(n)) => {
+                    Some(LabelItem::BsdInterleave(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_TRACKSKEW, V::Number(n)) => {
+                    Some(LabelItem::BsdTrackskew(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_CYLINDERSKEW, V::Number(n)) => {
+                    Some(LabelItem::BsdCylinderskew(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_HEADSWITCH, V::Number(n)) => {
+                    Some(LabelItem::BsdHeadswitch(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_bsd_BSD_LABELITEM_TRKSEEK, V::Number(n)) => {
+                    Some(LabelItem::BsdTrkseek(*n))
+                }
+                _ => None,
+            }
+        } else if self.is_labeltype(DiskLabel::Sgi) {
+            match (id, &value) {
+                (fdisk_sys::fdisk_labelitem_sgi_SGI_LABELITEM_PCYLCOUNT, V::Number(n)) => {
+                    Some(LabelItem::SgiPcylcount(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_sgi_SGI_LABELITEM_SPARECYL, V::Number(n)) => {
+                    Some(LabelItem::SgiSparecyl(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_sgi_SGI_LABELITEM_ILFACT, V::Number(n)) => {
+                    Some(LabelItem::SgiIlfact(*n))
+                }
+                (fdisk_sys::fdisk_labelitem_sgi_SGI_LABELITEM_BOOTFILE, V::String(s)) => {
+                    Some(LabelItem::SgiBootfile(s.clone()))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+        Ok(item.unwrap_or(LabelItem::Other {
+            id: id as i32,
+            name,
+            value,
+        }))
+    }
+}
@@ -1,13 +1,44 @@
 //! Rust wrappers for libfdisk
 
+pub mod ask;
+pub mod bsd;
+pub mod chromeos;
 pub mod context;
+pub mod diagnostic;
+pub mod dos;
+pub mod dps;
+pub mod error;
+pub mod freespace;
+pub mod gpt;
 pub mod iter;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod label;
+pub mod labelitem;
 pub mod partition;
+pub mod parttype;
+pub mod script;
+pub mod sgi;
+pub mod sun;
 pub mod table;
 
+pub use self::ask::{Ask, AskHandler};
+pub use self::bsd::BsdGeometry;
+pub use self::chromeos::ChromeOsAttributes;
 pub use self::context::Context;
+pub use self::diagnostic::{Diagnostic, Severity};
+pub use self::dos::DosPartition;
+pub use self::dps::{Architecture, DpsType};
+pub use self::error::{Error, Result};
+pub use self::freespace::FreeSpace;
+pub use self::gpt::GptAttributes;
 pub use self::iter::Iter;
-pub use self::label::Label;
+pub use self::label::{DiskId, DiskLabel, Label, Labels};
+pub use self::labelitem::{LabelItem, LabelItemValue};
 pub use self::partition::Partition;
+pub use self::partition::PartitionRef;
+pub use self::parttype::PartType;
+pub use self::script::Script;
+pub use self::sgi::SgiVolume;
 pub use self::table::Table;
+pub use nix::errno::Errno;
@@ -5,9 +5,13 @@
 //! template for fdisk_add_partition() or fdisk_set_partition() operations.
 
 use crate::context::Context;
-use anyhow::{anyhow, Result};
+use crate::error::{Error, Result};
+use crate::table::Table;
 use fdisk_sys;
 use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
 
 /// Generic label independent partition abstraction
 pub struct Partition {
@@ -32,14 +36,14 @@ impl Partition {
         unsafe { fdisk_sys::fdisk_reset_partition(self.ptr) }
     }
 
-    /// Return partition attributes in string format
-    pub fn attrs(&self) -> Option<String> {
+    /// Return partition attributes in string format, None if not set
+    pub fn attrs(&self) -> Result<Option<String>> {
         unsafe {
             let ptr = fdisk_sys::fdisk_partition_get_attrs(self.ptr);
             if ptr.is_null() {
-                return None;
+                return Ok(None);
             }
-            Some(CStr::from_ptr(ptr).to_str().unwrap().to_string())
+            Ok(Some(CStr::from_ptr(ptr).to_str()?.to_string()))
         }
     }
 
@@ -56,12 +60,9 @@ impl Partition {
         unsafe {
             let src = fdisk_sys::fdisk_partition_get_name(self.ptr);
             if src.is_null() {
-                return Err(anyhow!("no valid Name"));
-            }
-            match CStr::from_ptr(src).to_str() {
-                Ok(v) => Ok(v.to_string()),
-                Err(e) => Err(e.into()),
+                return Err(Error::NotFound("partition name"));
             }
+            Ok(CStr::from_ptr(src).to_str()?.to_string())
         }
     }
 
@@ -70,10 +71,7 @@ impl Partition {
         let mut p: usize = 0;
         match unsafe { fdisk_sys::fdisk_partition_get_parent(self.ptr, &mut p) } {
             0 => Ok(p),
-            v => Err(anyhow!(
-                "getting parent, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("getting parent", v)),
         }
     }
 
@@ -106,12 +104,9 @@ impl Partition {
         unsafe {
             let src = fdisk_sys::fdisk_partition_get_uuid(self.ptr);
             if src.is_null() {
-                return Err(anyhow!("no valid UUID"));
-            }
-            match CStr::from_ptr(src).to_str() {
-                Ok(v) => Ok(v.to_string()),
-                Err(e) => Err(e.into()),
+                return Err(Error::NotFound("partition UUID"));
             }
+            Ok(CStr::from_ptr(src).to_str()?.to_string())
         }
     }
 
@@ -160,78 +155,45 @@ impl Partition {
     pub fn set_partno(&self, partno: usize) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_partition_set_partno(self.ptr, partno) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting partno '{}', errno: {}",
-                partno,
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting partno", v)),
         }
     }
 
     pub fn set_size(&self, size: u64) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_partition_set_size(self.ptr, size) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting size '{}', errno: {}",
-                size,
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting size", v)),
         }
     }
 
     pub fn set_start(&self, start: u64) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_partition_set_start(self.ptr, start) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting start '{}', errno: {}",
-                start,
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting start", v)),
         }
     }
 
     pub fn set_attrs(&self, attrs: &str) -> Result<()> {
-        let value = match CString::new(attrs.as_bytes()) {
-            Ok(s) => s,
-            _ => return Err(anyhow!("converting {} to CString", attrs)),
-        };
+        let value = CString::new(attrs.as_bytes())?;
         match unsafe { fdisk_sys::fdisk_partition_set_attrs(self.ptr, value.as_ptr()) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting attributes '{}', errno: {}",
-                attrs,
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting attributes", v)),
         }
     }
 
     pub fn set_name(&self, name: &str) -> Result<()> {
-        let value = match CString::new(name.as_bytes()) {
-            Ok(s) => s,
-            _ => return Err(anyhow!("converting {} to CString", name)),
-        };
+        let value = CString::new(name.as_bytes())?;
         match unsafe { fdisk_sys::fdisk_partition_set_name(self.ptr, value.as_ptr()) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting name '{}', errno: {}",
-                name,
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting name", v)),
         }
     }
 
     pub fn set_uuid(&self, uuid: &str) -> Result<()> {
-        let value = match CString::new(uuid.as_bytes()) {
-            Ok(s) => s,
-            _ => return Err(anyhow!("converting {} to CString", uuid)),
-        };
+        let value = CString::new(uuid.as_bytes())?;
         match unsafe { fdisk_sys::fdisk_partition_set_uuid(self.ptr, value.as_ptr()) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting uuid '{}', errno: {}",
-                uuid,
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting uuid", v)),
         }
     }
 
@@ -242,10 +204,7 @@ impl Partition {
             fdisk_sys::fdisk_partition_size_explicit(self.ptr, if enable { 1 } else { 0 })
         } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "changing size explicit, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("changing size explicit", v)),
         }
     }
 
@@ -255,10 +214,29 @@ impl Partition {
             fdisk_sys::fdisk_partition_start_follow_default(self.ptr, if enable { 1 } else { 0 })
         } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "changing follow defaults, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("changing follow defaults", v)),
+        }
+    }
+
+    /// When partition used as a template for add_partition() use the first
+    /// free partition number, otherwise the number has to be set by set_partno().
+    pub fn partno_follow_default(&self, enable: bool) -> Result<()> {
+        match unsafe {
+            fdisk_sys::fdisk_partition_partno_follow_default(self.ptr, if enable { 1 } else { 0 })
+        } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("changing partno follow default", v)),
+        }
+    }
+
+    /// When partition used as a template for add_partition() use the end
+    /// of the free area, otherwise the size has to be set by set_size().
+    pub fn end_follow_default(&self, enable: bool) -> Result<()> {
+        match unsafe {
+            fdisk_sys::fdisk_partition_end_follow_default(self.ptr, if enable { 1 } else { 0 })
+        } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("changing end follow default", v)),
         }
     }
 
@@ -274,10 +252,7 @@ impl Partition {
     pub fn unset_partno(&self) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_partition_unset_partno(self.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting partno as undefined, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting partno as undefined", v)),
         }
     }
 
@@ -285,10 +260,7 @@ impl Partition {
     pub fn unset_size(&self) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_partition_unset_size(self.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting size as undefined, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting size as undefined", v)),
         }
     }
 
@@ -296,10 +268,7 @@ impl Partition {
     pub fn unset_start(&self) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_partition_unset_start(self.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting start as undefined, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("setting start as undefined", v)),
         }
     }
 }
@@ -316,6 +285,50 @@ impl Default for Partition {
     }
 }
 
+/// Partition entry borrowed from a [`Table`].
+/// The entry is owned by the table, so the view does not hold its own reference
+/// and can't outlive the table. Use [`PartitionRef::to_partition`] to get
+/// a standalone [`Partition`].
+pub struct PartitionRef<'tbl> {
+    inner: ManuallyDrop<Partition>,
+    _table: PhantomData<&'tbl Table>,
+}
+
+impl<'tbl> PartitionRef<'tbl> {
+    /// Wraps an entry of the table without incrementing reference counter.
+    ///
+    /// # Safety
+    /// `ptr` must be a non-null entry of a table that outlives `'tbl`.
+    pub(crate) unsafe fn from_ptr(ptr: *mut fdisk_sys::fdisk_partition) -> PartitionRef<'tbl> {
+        PartitionRef {
+            inner: ManuallyDrop::new(Partition { ptr }),
+            _table: PhantomData,
+        }
+    }
+
+    /// Return owned partition, increments reference counter of the entry.
+    pub fn to_partition(&self) -> Partition {
+        self.inner.ref_partition();
+        Partition {
+            ptr: self.inner.ptr,
+        }
+    }
+}
+
+impl<'tbl> Deref for PartitionRef<'tbl> {
+    type Target = Partition;
+
+    fn deref(&self) -> &Partition {
+        &self.inner
+    }
+}
+
+impl<'tbl> From<PartitionRef<'tbl>> for Partition {
+    fn from(pa: PartitionRef<'tbl>) -> Partition {
+        pa.to_partition()
+    }
+}
+
 impl Context {
     /// Modifies disklabel according to setting with in pa .
     /// # Arguments
@@ -324,11 +337,31 @@ impl Context {
     pub fn set_partition(&self, no: usize, pt: &Partition) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_set_partition(self.ptr, no, pt.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "setting partno '{}', errno: {}",
-                no,
-                nix::errno::from_i32(v)
-            )),
+            v => Err(self.error("setting partition", v)),
+        }
+    }
+
+    /// Adds partition to disklabel and return number of the new partition.
+    /// The template may be incomplete, in this case the missing values are
+    /// set to defaults (see start_follow_default(), partno_follow_default()
+    /// and size_explicit()).
+    /// # Arguments
+    /// * `pt` - partition template
+    pub fn add_partition(&self, pt: &Partition) -> Result<usize> {
+        let mut partno: usize = 0;
+        match unsafe { fdisk_sys::fdisk_add_partition(self.ptr, pt.ptr, &mut partno) } {
+            0 => Ok(partno),
+            v => Err(self.error("adding partition", v)),
+        }
+    }
+
+    /// Deletes partition from disklabel.
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    pub fn delete_partition(&self, partno: usize) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_delete_partition(self.ptr, partno) } {
+            0 => Ok(()),
+            v => Err(self.error("deleting partition", v)),
         }
     }
 
@@ -336,10 +369,30 @@ impl Context {
     pub fn delete_all_partitions(&self) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_delete_all_partitions(self.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "deleting all partitions, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(self.error("deleting all partitions", v)),
+        }
+    }
+
+    /// Return partition from disklabel
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    pub fn partition(&self, partno: usize) -> Result<Partition> {
+        let mut pa = Partition::new();
+        match unsafe { fdisk_sys::fdisk_get_partition(self.ptr, partno, &mut pa.ptr) } {
+            0 => Ok(pa),
+            v => Err(self.error("getting partition", v)),
+        }
+    }
+
+    /// Toggles label specific flag of the partition, e.g. DOS_FLAG_ACTIVE
+    /// or GPT_FLAG_REQUIRED.
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    /// * `flag` - label specific flag
+    pub fn toggle_partition_flag(&self, partno: usize, flag: u64) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_toggle_partition_flag(self.ptr, partno, flag as _) } {
+            0 => Ok(()),
+            v => Err(self.error("toggling partition flag", v)),
         }
     }
 }
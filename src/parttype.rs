@@ -0,0 +1,179 @@
+//! Partition type — label specific partition type, e.g. numeric code for
+//! DOS or type GUID for GPT. The label drivers provide static lists of the
+//! known types, the unknown types are allocated on demand.
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::label::Label;
+use crate::partition::Partition;
+use fdisk_sys;
+use std::ffi::{CStr, CString};
+
+/// Partition type
+pub struct PartType {
+    pub(crate) ptr: *mut fdisk_sys::fdisk_parttype,
+}
+
+impl PartType {
+    /// Wraps the type and increments reference counter. Static types
+    /// provided by label drivers are not reference counted.
+    ///
+    /// # Safety
+    /// `ptr` must be a valid non-null partition type.
+    pub(crate) unsafe fn from_ptr(ptr: *mut fdisk_sys::fdisk_parttype) -> PartType {
+        fdisk_sys::fdisk_ref_parttype(ptr);
+        PartType { ptr }
+    }
+
+    /// Return newly allocated type which is not known by any label driver
+    /// # Arguments
+    /// * `code` - type code (e.g. for DOS)
+    /// * `typestr` - type string (e.g. GUID for GPT)
+    pub fn new_unknown(code: u32, typestr: &str) -> Result<PartType> {
+        let typestr = CString::new(typestr.as_bytes())?;
+        let ptr = unsafe { fdisk_sys::fdisk_new_unknown_parttype(code, typestr.as_ptr()) };
+        if ptr.is_null() {
+            return Err(Error::last("allocating partition type"));
+        }
+        Ok(PartType { ptr })
+    }
+
+    /// Return type code, zero for labels without numeric codes (e.g. GPT)
+    pub fn code(&self) -> u32 {
+        unsafe { fdisk_sys::fdisk_parttype_get_code(self.ptr) }
+    }
+
+    /// Return human readable type name
+    pub fn name(&self) -> Option<String> {
+        unsafe {
+            let src = fdisk_sys::fdisk_parttype_get_name(self.ptr);
+            if src.is_null() {
+                return None;
+            }
+            Some(CStr::from_ptr(src).to_string_lossy().into_owned())
+        }
+    }
+
+    /// Return type string (e.g. GUID for GPT)
+    pub fn typestr(&self) -> Option<String> {
+        unsafe {
+            let src = fdisk_sys::fdisk_parttype_get_string(self.ptr);
+            if src.is_null() {
+                return None;
+            }
+            Some(CStr::from_ptr(src).to_string_lossy().into_owned())
+        }
+    }
+
+    /// Return true if the type is not known by label driver
+    pub fn is_unknown(&self) -> bool {
+        matches!(unsafe { fdisk_sys::fdisk_parttype_is_unknown(self.ptr) }, 1)
+    }
+}
+
+impl Clone for PartType {
+    fn clone(&self) -> PartType {
+        unsafe { PartType::from_ptr(self.ptr) }
+    }
+}
+
+impl Drop for PartType {
+    fn drop(&mut self) {
+        unsafe { fdisk_sys::fdisk_unref_parttype(self.ptr) }
+    }
+}
+
+impl Label<'_> {
+    /// Return number of partition types supported by the label driver
+    pub fn nparttypes(&self) -> usize {
+        unsafe { fdisk_sys::fdisk_label_get_nparttypes(self.ptr) }
+    }
+
+    /// Return n-th partition type supported by the label driver
+    pub fn parttype(&self, n: usize) -> Option<PartType> {
+        let ptr = unsafe { fdisk_sys::fdisk_label_get_parttype(self.ptr, n) };
+        if ptr.is_null() {
+            return None;
+        }
+        Some(unsafe { PartType::from_ptr(ptr) })
+    }
+
+    /// Return all partition types supported by the label driver
+    pub fn parttypes(&self) -> Vec<PartType> {
+        (0..self.nparttypes())
+            .filter_map(|n| self.parttype(n))
+            .collect()
+    }
+
+    /// Search for partition type by code (e.g. 0x83 for DOS)
+    pub fn parttype_from_code(&self, code: u32) -> Option<PartType> {
+        let ptr = unsafe { fdisk_sys::fdisk_label_get_parttype_from_code(self.ptr, code) };
+        if ptr.is_null() {
+            return None;
+        }
+        Some(unsafe { PartType::from_ptr(ptr) })
+    }
+
+    /// Search for partition type by type string (e.g. GUID for GPT)
+    pub fn parttype_from_string(&self, typestr: &str) -> Result<Option<PartType>> {
+        let typestr = CString::new(typestr.as_bytes())?;
+        let ptr =
+            unsafe { fdisk_sys::fdisk_label_get_parttype_from_string(self.ptr, typestr.as_ptr()) };
+        if ptr.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(unsafe { PartType::from_ptr(ptr) }))
+    }
+
+    /// Parses partition type the same way as `sfdisk --part-type` does, the string
+    /// may be a code, GUID, shortcut (e.g. "L"), alias (e.g. "linux") or type name.
+    /// Strings not known by the label driver are returned as unknown type.
+    pub fn parse_parttype(&self, s: &str) -> Result<PartType> {
+        let value = CString::new(s.as_bytes())?;
+        let flags = fdisk_sys::fdisk_parttype_parser_flags_FDISK_PARTTYPE_PARSE_DATA
+            | fdisk_sys::fdisk_parttype_parser_flags_FDISK_PARTTYPE_PARSE_SHORTCUT
+            | fdisk_sys::fdisk_parttype_parser_flags_FDISK_PARTTYPE_PARSE_ALIAS
+            | fdisk_sys::fdisk_parttype_parser_flags_FDISK_PARTTYPE_PARSE_NAME
+            | fdisk_sys::fdisk_parttype_parser_flags_FDISK_PARTTYPE_PARSE_DEPRECATED;
+        let ptr = unsafe {
+            fdisk_sys::fdisk_label_advparse_parttype(self.ptr, value.as_ptr(), flags as i32)
+        };
+        if ptr.is_null() {
+            return Err(Error::NotFound("partition type"));
+        }
+        // the parser returns either static or newly allocated type
+        Ok(PartType { ptr })
+    }
+}
+
+impl Partition {
+    /// Return partition type
+    pub fn part_type(&self) -> Option<PartType> {
+        let ptr = unsafe { fdisk_sys::fdisk_partition_get_type(self.ptr) };
+        if ptr.is_null() {
+            return None;
+        }
+        Some(unsafe { PartType::from_ptr(ptr) })
+    }
+
+    /// Sets partition type, used as a template for add_partition() or set_partition()
+    pub fn set_type(&self, t: &PartType) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_partition_set_type(self.ptr, t.ptr) } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("setting partition type", v)),
+        }
+    }
+}
+
+impl Context {
+    /// Changes type of the partition in disklabel.
+    /// # Arguments
+    /// * `partno` - partition number (0 is the first partition)
+    /// * `t` - new partition type
+    pub fn set_partition_type(&self, partno: usize, t: &PartType) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_set_partition_type(self.ptr, partno, t.ptr) } {
+            0 => Ok(()),
+            v => Err(self.error("setting partition type", v)),
+        }
+    }
+}
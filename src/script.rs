@@ -0,0 +1,499 @@
+//! Script — text based description of partition table.
+//! The script is compatible with sfdisk dumps, it's possible to read the
+//! script from the current disklabel, parse it from a file or string,
+//! write it in sfdisk dump format and apply it to a device.
+
+use crate::context::Context;
+use crate::error::{Error, Result};
+use fdisk_sys;
+use std::ffi::{CStr, CString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// sfdisk compatible script
+pub struct Script {
+    pub(crate) ptr: *mut fdisk_sys::fdisk_script,
+}
+
+/// stdio stream closed on drop
+struct Stream(*mut libc::FILE);
+
+impl Stream {
+    fn as_ptr(&self) -> *mut fdisk_sys::FILE {
+        self.0 as *mut fdisk_sys::FILE
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        unsafe { libc::fclose(self.0) };
+    }
+}
+
+impl Script {
+    /// Return new empty script bound to the context. The context is used
+    /// to parse label specific data (e.g. partition types).
+    pub fn new(cxt: &Context) -> Result<Script> {
+        let ptr = unsafe { fdisk_sys::fdisk_new_script(cxt.ptr) };
+        if ptr.is_null() {
+            return Err(Error::last("allocating script"));
+        }
+        Ok(Script { ptr })
+    }
+
+    /// Return script with the current in-memory disklabel of the context.
+    pub fn from_context(cxt: &Context) -> Result<Script> {
+        let script = Script::new(cxt)?;
+        match unsafe { fdisk_sys::fdisk_script_read_context(script.ptr, cxt.ptr) } {
+            0 => Ok(script),
+            v => Err(cxt.error("reading context to script", v)),
+        }
+    }
+
+    /// Return script parsed from the file.
+    /// # Arguments
+    /// * `path` - sfdisk dump
+    pub fn from_file<P: AsRef<Path>>(cxt: &Context, path: P) -> Result<Script> {
+        let path = path.as_ref();
+        let name = CString::new(path.as_os_str().as_bytes())?;
+        let ptr = unsafe { fdisk_sys::fdisk_new_script_from_file(cxt.ptr, name.as_ptr()) };
+        if ptr.is_null() {
+            return Err(Error::last("reading script").with_device(Some(path.to_path_buf())));
+        }
+        Ok(Script { ptr })
+    }
+
+    /// Return script parsed from the string.
+    /// # Arguments
+    /// * `dump` - sfdisk dump
+    pub fn parse(cxt: &Context, dump: &str) -> Result<Script> {
+        let script = Script::new(cxt)?;
+        if dump.is_empty() {
+            return Ok(script);
+        }
+        let stream = unsafe {
+            libc::fmemopen(
+                dump.as_ptr() as *mut libc::c_void,
+                dump.len(),
+                b"r\0".as_ptr() as *const libc::c_char,
+            )
+        };
+        if stream.is_null() {
+            return Err(Error::last("opening script"));
+        }
+        let stream = Stream(stream);
+        match unsafe { fdisk_sys::fdisk_script_read_file(script.ptr, stream.as_ptr()) } {
+            0 => Ok(script),
+            v => Err(Error::fdisk("parsing script", v)),
+        }
+    }
+
+    /// Return the script in sfdisk dump format.
+    pub fn dump(&self) -> Result<String> {
+        let mut buf: *mut libc::c_char = std::ptr::null_mut();
+        let mut size: libc::size_t = 0;
+        let stream = unsafe { libc::open_memstream(&mut buf, &mut size) };
+        if stream.is_null() {
+            return Err(Error::last("opening script"));
+        }
+        let rc = unsafe { fdisk_sys::fdisk_script_write_file(self.ptr, stream as *mut _) };
+        // the buffer is valid after the stream is closed
+        unsafe { libc::fclose(stream) };
+        let dump = unsafe { std::slice::from_raw_parts(buf as *const u8, size) };
+        let dump = std::str::from_utf8(dump).map(|s| s.to_string());
+        unsafe { libc::free(buf as *mut libc::c_void) };
+        match rc {
+            0 => Ok(dump?),
+            v => Err(Error::fdisk("writing script", v)),
+        }
+    }
+
+    /// Writes the script in sfdisk dump format to the file.
+    /// # Arguments
+    /// * `path` - output file, truncated if exists
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let name = CString::new(path.as_os_str().as_bytes())?;
+        let stream = unsafe { libc::fopen(name.as_ptr(), b"w\0".as_ptr() as *const libc::c_char) };
+        if stream.is_null() {
+            return Err(Error::last("opening script").with_device(Some(path.to_path_buf())));
+        }
+        let stream = Stream(stream);
+        match unsafe { fdisk_sys::fdisk_script_write_file(self.ptr, stream.as_ptr()) } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("writing script", v).with_device(Some(path.to_path_buf()))),
+        }
+    }
+
+    /// Enables or disables JSON output format, the JSON is the same as
+    /// `sfdisk --json` prints. Affects dump() and write_file().
+    pub fn enable_json(&self, enable: bool) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_script_enable_json(self.ptr, enable as i32) } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("changing JSON output", v)),
+        }
+    }
+
+    /// Return value of the header, e.g. "label" or "first-lba"
+    pub fn header(&self, name: &str) -> Result<Option<String>> {
+        let name = CString::new(name.as_bytes())?;
+        unsafe {
+            let src = fdisk_sys::fdisk_script_get_header(self.ptr, name.as_ptr());
+            if src.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(CStr::from_ptr(src).to_str()?.to_string()))
+        }
+    }
+
+    /// Sets the header, the old value is replaced.
+    /// # Arguments
+    /// * `name` - header name
+    /// * `value` - header value
+    pub fn set_header(&self, name: &str, value: &str) -> Result<()> {
+        let name = CString::new(name.as_bytes())?;
+        let value = CString::new(value.as_bytes())?;
+        match unsafe { fdisk_sys::fdisk_script_set_header(self.ptr, name.as_ptr(), value.as_ptr()) }
+        {
+            0 => Ok(()),
+            v => Err(Error::fdisk("setting script header", v)),
+        }
+    }
+
+    /// Removes the header from the script
+    pub fn remove_header(&self, name: &str) -> Result<()> {
+        let name = CString::new(name.as_bytes())?;
+        match unsafe {
+            fdisk_sys::fdisk_script_set_header(self.ptr, name.as_ptr(), std::ptr::null())
+        } {
+            0 => Ok(()),
+            v => Err(Error::fdisk("removing script header", v)),
+        }
+    }
+
+    /// Return numeric header, the value is a plain decimal number
+    fn header_u64(&self, name: &str) -> Result<Option<u64>> {
+        self.header_parsed(name, |value| value.parse().ok())
+    }
+
+    /// Return size header, the value may use size suffixes (e.g. "1MiB")
+    fn header_size(&self, name: &str) -> Result<Option<u64>> {
+        self.header_parsed(name, parse_size)
+    }
+
+    /// Return header converted by the parser, InvalidHeader if the parser fails
+    fn header_parsed<F: Fn(&str) -> Option<u64>>(
+        &self,
+        name: &str,
+        parse: F,
+    ) -> Result<Option<u64>> {
+        match self.header(name)? {
+            Some(value) => match parse(&value) {
+                Some(v) => Ok(Some(v)),
+                None => Err(Error::InvalidHeader {
+                    name: name.to_string(),
+                    value,
+                }),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Return label name (e.g. "gpt")
+    pub fn label(&self) -> Result<Option<String>> {
+        self.header("label")
+    }
+
+    /// Sets label name
+    pub fn set_label<L: AsRef<str>>(&self, name: L) -> Result<()> {
+        self.set_header("label", name.as_ref())
+    }
+
+    /// Return disk identifier (GUID for GPT, hex signature for DOS)
+    pub fn label_id(&self) -> Result<Option<String>> {
+        self.header("label-id")
+    }
+
+    /// Sets disk identifier, applied by apply_script()
+    pub fn set_label_id(&self, id: &str) -> Result<()> {
+        self.set_header("label-id", id)
+    }
+
+    /// Return device name
+    pub fn device(&self) -> Result<Option<String>> {
+        self.header("device")
+    }
+
+    /// Sets device name
+    pub fn set_device<P: AsRef<Path>>(&self, device: P) -> Result<()> {
+        self.set_header("device", &device.as_ref().to_string_lossy())
+    }
+
+    /// Return unit, the only supported unit is "sectors"
+    pub fn unit(&self) -> Result<Option<String>> {
+        self.header("unit")
+    }
+
+    /// Sets unit
+    pub fn set_unit(&self, unit: &str) -> Result<()> {
+        self.set_header("unit", unit)
+    }
+
+    /// Return first usable LBA
+    pub fn first_lba(&self) -> Result<Option<u64>> {
+        self.header_u64("first-lba")
+    }
+
+    /// Sets first usable LBA
+    pub fn set_first_lba(&self, lba: u64) -> Result<()> {
+        self.set_header("first-lba", &lba.to_string())
+    }
+
+    /// Return last usable LBA
+    pub fn last_lba(&self) -> Result<Option<u64>> {
+        self.header_u64("last-lba")
+    }
+
+    /// Sets last usable LBA
+    pub fn set_last_lba(&self, lba: u64) -> Result<()> {
+        self.set_header("last-lba", &lba.to_string())
+    }
+
+    /// Return number of entries in GPT partition entries array
+    pub fn table_length(&self) -> Result<Option<u64>> {
+        self.header_u64("table-length")
+    }
+
+    /// Sets number of entries in GPT partition entries array
+    pub fn set_table_length(&self, entries: u64) -> Result<()> {
+        self.set_header("table-length", &entries.to_string())
+    }
+
+    /// Return grain in bytes used to align partitions
+    pub fn grain(&self) -> Result<Option<u64>> {
+        self.header_size("grain")
+    }
+
+    /// Sets grain in bytes used to align partitions
+    pub fn set_grain(&self, grain: u64) -> Result<()> {
+        self.set_header("grain", &grain.to_string())
+    }
+
+    /// Return logical sector size in bytes
+    pub fn sector_size(&self) -> Result<Option<u64>> {
+        self.header_u64("sector-size")
+    }
+
+    /// Sets logical sector size in bytes
+    pub fn set_sector_size(&self, size: u64) -> Result<()> {
+        self.set_header("sector-size", &size.to_string())
+    }
+
+    /// Return number of lines read from the script
+    pub fn nlines(&self) -> usize {
+        unsafe { fdisk_sys::fdisk_script_get_nlines(self.ptr) as usize }
+    }
+
+    /// Return true if the script contains "label" header
+    pub fn has_force_label(&self) -> bool {
+        matches!(
+            unsafe { fdisk_sys::fdisk_script_has_force_label(self.ptr) },
+            1
+        )
+    }
+}
+
+/// Return true for the same characters as C isspace()
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\x0b' | '\x0c' | '\r')
+}
+
+/// Parses unsigned number like strtoumax() with base 0, "0x" prefix is hex
+/// and "0" prefix is octal. Return the number (None on overflow) and the rest
+/// of the string, or None if there are no digits.
+fn strtoumax(value: &str) -> Option<(Option<u64>, &str)> {
+    let s = value.trim_start_matches(is_space);
+    let s = s.strip_prefix('+').unwrap_or(s);
+    let (radix, digits) = match s.as_bytes() {
+        [b'0', b'x', c, ..] | [b'0', b'X', c, ..] if c.is_ascii_hexdigit() => (16, &s[2..]),
+        [b'0', ..] => (8, s),
+        _ => (10, s),
+    };
+    let len = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    if len == 0 {
+        return None;
+    }
+    let num = digits[..len].chars().try_fold(0u64, |num, c| {
+        num.checked_mul(radix.into())?
+            .checked_add(c.to_digit(radix)?.into())
+    });
+    Some((num, &digits[len..]))
+}
+
+/// Multiplies the number by base^power, the number is the last value which
+/// fits to u64 on overflow.
+fn scale_by_power(num: &mut u64, base: u64, power: u32) -> Option<()> {
+    for _ in 0..power {
+        *num = num.checked_mul(base)?;
+    }
+    Some(())
+}
+
+/// Parses number with optional size suffix the same way as libfdisk parses
+/// grain (util-linux parse_size()). The number may be hex or octal, "K", "KiB"
+/// are powers of 1024, "KB" is power of 1000 and the number may have decimal
+/// fraction if the suffix is used, e.g. "1.5MiB".
+fn parse_size(value: &str) -> Option<u64> {
+    if value.trim_start_matches(is_space).starts_with('-') {
+        return None;
+    }
+    let (num, mut rest) = strtoumax(value)?;
+    let mut num = num?;
+    if rest.is_empty() {
+        return Some(num);
+    }
+    let mut frac = 0u64;
+    let mut frac_zeros = 0;
+    let base = loop {
+        let suffix = rest.as_bytes();
+        match suffix {
+            [_, b'i', b'B'] | [_, b'i', b'b'] => break 1024,
+            [_, b'B'] | [_, b'b'] => break 1000,
+            [_] => break 1024,
+            [b'.', _, ..] if frac == 0 => {
+                let digits = rest[1..].trim_start_matches('0');
+                frac_zeros += rest.len() - 1 - digits.len();
+                rest = digits;
+                if digits.starts_with(|c: char| c.is_ascii_digit()) {
+                    let (f, end) = strtoumax(digits)?;
+                    frac = f?;
+                    if end.is_empty() {
+                        // fraction without suffix
+                        return None;
+                    }
+                    rest = end;
+                }
+            }
+            _ => return None,
+        }
+    };
+    let c = rest.chars().next()?;
+    let power = "KMGTPEZY".find(c).or_else(|| "kmgtpezy".find(c))? as u32 + 1;
+    scale_by_power(&mut num, base, power)?;
+    if frac != 0 {
+        let mut frac_base = 1;
+        // the same as libfdisk, the overflow is ignored here
+        let _ = scale_by_power(&mut frac_base, base, power);
+        // divisor of the last digit, e.g. 100 for "0.05" and 1000 for "0.054"
+        let mut frac_div: u64 = 10;
+        while frac_div < frac {
+            frac_div = frac_div.wrapping_mul(10);
+        }
+        for _ in 0..frac_zeros {
+            frac_div = frac_div.wrapping_mul(10);
+        }
+        let mut frac_poz: u64 = 1;
+        while frac != 0 {
+            let seg = frac % 10;
+            let seg_div = frac_div / frac_poz;
+            frac /= 10;
+            frac_poz = frac_poz.wrapping_mul(10);
+            if seg != 0 && seg_div / seg != 0 {
+                num = num.wrapping_add(frac_base / (seg_div / seg));
+            }
+        }
+    }
+    Some(num)
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        unsafe { fdisk_sys::fdisk_unref_script(self.ptr) }
+    }
+}
+
+impl Context {
+    /// Creates a new disklabel and partitions described by the script.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `script` - script to apply
+    pub fn apply_script(&self, script: &Script) -> Result<()> {
+        match unsafe { fdisk_sys::fdisk_apply_script(self.ptr, script.ptr) } {
+            0 => Ok(()),
+            v => Err(self.error("applying script", v)),
+        }
+    }
+
+    /// Return the in-memory disklabel as JSON, the same as `sfdisk --json` prints.
+    pub fn dump_json(&self) -> Result<String> {
+        let script = Script::from_context(self)?;
+        script.enable_json(true)?;
+        script.dump()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_size;
+
+    #[test]
+    fn parse_size_plain() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("2048"), Some(2048));
+        assert_eq!(parse_size(" +512"), Some(512));
+        assert_eq!(parse_size("0x200000"), Some(0x20_0000));
+        assert_eq!(parse_size("0X10"), Some(16));
+        assert_eq!(parse_size("010"), Some(8));
+    }
+
+    #[test]
+    fn parse_size_suffixes() {
+        assert_eq!(parse_size("1K"), Some(1024));
+        assert_eq!(parse_size("1k"), Some(1024));
+        assert_eq!(parse_size("1KiB"), Some(1024));
+        assert_eq!(parse_size("1Kib"), Some(1024));
+        assert_eq!(parse_size("1KB"), Some(1000));
+        assert_eq!(parse_size("1Kb"), Some(1000));
+        assert_eq!(parse_size("1MiB"), Some(1024 * 1024));
+        assert_eq!(parse_size("1Mib"), Some(1024 * 1024));
+        assert_eq!(parse_size("2MB"), Some(2_000_000));
+        assert_eq!(parse_size("1E"), Some(1 << 60));
+        assert_eq!(parse_size("0x10K"), Some(16 * 1024));
+        assert_eq!(parse_size("0Y"), Some(0));
+    }
+
+    #[test]
+    fn parse_size_fraction() {
+        assert_eq!(parse_size("1.5K"), Some(1536));
+        assert_eq!(parse_size("0.5MiB"), Some(512 * 1024));
+        assert_eq!(parse_size("1.25MB"), Some(1_250_000));
+        assert_eq!(parse_size("2.05KB"), Some(2050));
+        assert_eq!(parse_size("1.K"), Some(1024));
+        assert_eq!(parse_size("1.5"), None);
+        assert_eq!(parse_size("1."), None);
+    }
+
+    #[test]
+    fn parse_size_overflow() {
+        assert_eq!(parse_size("16E"), None);
+        assert_eq!(parse_size("1Z"), None);
+        assert_eq!(parse_size("99999999999999999999"), None);
+        assert_eq!(parse_size("0x10000000000000000"), None);
+        assert_eq!(parse_size("18446744073709551615"), Some(u64::MAX));
+    }
+
+    #[test]
+    fn parse_size_garbage() {
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("K"), None);
+        assert_eq!(parse_size("1X"), None);
+        assert_eq!(parse_size("1KiBs"), None);
+        assert_eq!(parse_size("512 "), None);
+        assert_eq!(parse_size("-1"), None);
+        assert_eq!(parse_size(" -1"), None);
+        assert_eq!(parse_size("08"), None);
+        assert_eq!(parse_size("0x"), None);
+    }
+}
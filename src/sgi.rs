@@ -0,0 +1,111 @@
+//! SGI — label specific functions for SGI disklabel. The label contains
+//! volume directory with files stored in the volume header partition,
+//! e.g. "sgilabel" info block or standalone boot programs.
+
+use crate::ask::Reply;
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::label::DiskLabel;
+use fdisk_sys;
+
+/// SGI disklabel magic number
+const SGI_LABEL_MAGIC: u32 = 0x0be5_a941;
+
+/// Offset of the volume directory in the disklabel
+const SGI_VOLUME_OFFSET: usize = 72;
+
+/// Number of entries in the volume directory
+const SGI_MAX_VOLUMES: usize = 15;
+
+/// Size of the volume directory entry
+const SGI_VOLUME_SIZE: usize = 16;
+
+/// Size of the disklabel
+const SGI_LABEL_SIZE: usize = 512;
+
+/// File in the volume header partition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SgiVolume {
+    /// File name, at most 8 bytes
+    pub name: String,
+    /// First block (512 bytes) of the file
+    pub block: u32,
+    /// File size in bytes
+    pub size: u32,
+}
+
+/// Return big-endian u32 from the buffer
+fn be32(buf: &[u8], offset: usize) -> u32 {
+    let mut b = [0u8; 4];
+    b.copy_from_slice(&buf[offset..offset + 4]);
+    u32::from_be_bytes(b)
+}
+
+impl Context {
+    /// Sets boot file, the path has to be absolute with at most 16 bytes,
+    /// e.g. "/unix". The changes are in-memory only, use write_disklabel() to
+    /// write them to the device.
+    /// # Arguments
+    /// * `path` - boot file path
+    pub fn sgi_set_bootfile(&self, path: &str) -> Result<()> {
+        // libfdisk asserts the label type
+        if !self.is_labeltype(DiskLabel::Sgi) {
+            return Err(Error::NotFound("SGI label"));
+        }
+        self.with_replies(vec![Reply::String(path.to_string())], || {
+            // 1 means the boot file is unchanged
+            match unsafe { fdisk_sys::fdisk_sgi_set_bootfile(self.ptr) } {
+                0 | 1 => Ok(()),
+                v => Err(self.error("setting SGI boot file", v)),
+            }
+        })
+    }
+
+    /// Creates "sgilabel" info block in the volume directory, the block is
+    /// written to the second sector by write_disklabel().
+    pub fn sgi_create_info(&self) -> Result<()> {
+        if !self.is_labeltype(DiskLabel::Sgi) {
+            return Err(Error::NotFound("SGI label"));
+        }
+        match unsafe { fdisk_sys::fdisk_sgi_create_info(self.ptr) } {
+            0 => Ok(()),
+            v => Err(self.error("creating SGI info", v)),
+        }
+    }
+
+    /// Return files of the volume directory as written on the device. libfdisk
+    /// does not provide the in-memory directory, so changes like sgi_create_info()
+    /// are not visible before write_disklabel().
+    pub fn sgi_ondisk_volumes(&self) -> Result<Vec<SgiVolume>> {
+        if !self.is_labeltype(DiskLabel::Sgi) {
+            return Err(Error::NotFound("SGI label"));
+        }
+        if self.fd() < 0 {
+            return Err(Error::NotFound("assigned device"));
+        }
+        let mut buf = [0u8; SGI_LABEL_SIZE];
+        match nix::sys::uio::pread(self.fd(), &mut buf, 0) {
+            Ok(SGI_LABEL_SIZE) => {}
+            Ok(_) => return Err(self.error("reading SGI disklabel", -libc::EIO)),
+            Err(e) => return Err(self.error("reading SGI disklabel", -(e as i32))),
+        }
+        if be32(&buf, 0) != SGI_LABEL_MAGIC {
+            return Err(Error::NotFound("SGI disklabel on device"));
+        }
+        let mut volumes = Vec::new();
+        for i in 0..SGI_MAX_VOLUMES {
+            let entry = &buf[SGI_VOLUME_OFFSET + i * SGI_VOLUME_SIZE..][..SGI_VOLUME_SIZE];
+            let name = &entry[..8];
+            let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+            if len == 0 {
+                continue;
+            }
+            volumes.push(SgiVolume {
+                name: String::from_utf8_lossy(&name[..len]).into_owned(),
+                block: be32(entry, 8),
+                size: be32(entry, 12),
+            });
+        }
+        Ok(volumes)
+    }
+}
@@ -0,0 +1,97 @@
+//! SUN — label specific functions for SUN disklabel. The geometry
+//! parameters are interactive in libfdisk, the values are passed to the
+//! library dialogs and checked against the ranges the library asks for.
+
+use crate::ask::Reply;
+use crate::context::Context;
+use crate::error::{Error, Result};
+use crate::label::DiskLabel;
+use crate::labelitem::LabelItem;
+use fdisk_sys;
+
+impl Context {
+    /// Answers the dialog of SUN label function by the value
+    fn sun_set(
+        &self,
+        op: &'static str,
+        value: u16,
+        f: unsafe extern "C" fn(*mut fdisk_sys::fdisk_context) -> i32,
+    ) -> Result<()> {
+        // libfdisk asserts the label type
+        if !self.is_labeltype(DiskLabel::Sun) {
+            return Err(Error::NotFound("SUN label"));
+        }
+        self.with_replies(vec![Reply::Number(value.into())], || {
+            match unsafe { f(self.ptr) } {
+                0 => Ok(()),
+                v => Err(self.error(op, v)),
+            }
+        })
+    }
+
+    /// Sets number of alternate cylinders.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `cylinders` - alternate cylinders
+    pub fn sun_set_alt_cyl(&self, cylinders: u16) -> Result<()> {
+        self.sun_set(
+            "setting SUN alternate cylinders",
+            cylinders,
+            fdisk_sys::fdisk_sun_set_alt_cyl,
+        )
+    }
+
+    /// Sets number of extra sectors per cylinder.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `sectors` - extra sectors, less than sectors per track
+    pub fn sun_set_xcyl(&self, sectors: u16) -> Result<()> {
+        self.sun_set(
+            "setting SUN extra sectors",
+            sectors,
+            fdisk_sys::fdisk_sun_set_xcyl,
+        )
+    }
+
+    /// Sets interleave factor.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `factor` - interleave factor, 1 to 32
+    pub fn sun_set_ilfact(&self, factor: u16) -> Result<()> {
+        self.sun_set(
+            "setting SUN interleave factor",
+            factor,
+            fdisk_sys::fdisk_sun_set_ilfact,
+        )
+    }
+
+    /// Sets rotation speed.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `rpm` - rotation speed in rpm, at least 1
+    pub fn sun_set_rspeed(&self, rpm: u16) -> Result<()> {
+        self.sun_set(
+            "setting SUN rotation speed",
+            rpm,
+            fdisk_sys::fdisk_sun_set_rspeed,
+        )
+    }
+
+    /// Sets number of physical cylinders.
+    /// The changes are in-memory only, use write_disklabel() to write them to the device.
+    /// # Arguments
+    /// * `cylinders` - physical cylinders
+    pub fn sun_set_pcylcount(&self, cylinders: u16) -> Result<()> {
+        let op = "setting SUN physical cylinders";
+        self.sun_set(op, cylinders, fdisk_sys::fdisk_sun_set_pcylcount)?;
+        // some libfdisk versions ignore the answer and return success
+        let pcyl = self.label_items()?.into_iter().find_map(|item| match item {
+            LabelItem::SunPcyl(v) => Some(v),
+            _ => None,
+        });
+        match pcyl {
+            Some(v) if v == u64::from(cylinders) => Ok(()),
+            _ => Err(self.error(op, -libc::ENOTSUP)),
+        }
+    }
+}
@@ -1,9 +1,9 @@
 //! Container for fdisk partitions. The container does not have any
 //! real connection with label (partition table) and with real on-disk data.
 
+use crate::error::{Error, Result};
 use crate::iter::Iter;
-use crate::partition::Partition;
-use anyhow::{anyhow, Result};
+use crate::partition::{Partition, PartitionRef};
 use fdisk_sys;
 
 /// Container for fdisk partitions
@@ -27,13 +27,10 @@ impl Table {
     /// Removes all entries (partitions) from the table. The parititons
     /// with zero reference count will be deallocated.
     /// This function does not modify partition table.
-    pub fn reset_table(&self) -> Result<()> {
+    pub fn reset_table(&mut self) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_reset_table(self.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "resetting table, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("resetting table", v)),
         }
     }
 
@@ -42,13 +39,10 @@ impl Table {
     /// if you want to keep the pa referenced by the table only.
     /// # Arguments
     /// * `pa` - partition
-    pub fn add_partition(&self, pa: &mut Partition) -> Result<()> {
+    pub fn add_partition(&mut self, pa: &Partition) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_table_add_partition(self.ptr, pa.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "adding partition, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("adding partition", v)),
         }
     }
 
@@ -58,21 +52,21 @@ impl Table {
     }
 
     /// Return n-th entry from table
-    pub fn partition(&self, n: usize) -> Option<Partition> {
+    pub fn partition(&self, n: usize) -> Option<PartitionRef<'_>> {
         let ptr = unsafe { fdisk_sys::fdisk_table_get_partition(self.ptr, n) };
         if ptr.is_null() {
             return None;
         }
-        Some(Partition { ptr })
+        Some(unsafe { PartitionRef::from_ptr(ptr) })
     }
 
     /// Return partition with partno.
-    pub fn partition_by_partno(&self, partno: usize) -> Option<Partition> {
+    pub fn partition_by_partno(&self, partno: usize) -> Option<PartitionRef<'_>> {
         let ptr = unsafe { fdisk_sys::fdisk_table_get_partition_by_partno(self.ptr, partno) };
         if ptr.is_null() {
             return None;
         }
-        Some(Partition { ptr })
+        Some(unsafe { PartitionRef::from_ptr(ptr) })
     }
 
     /// Return true if the table is without filesystems
@@ -86,13 +80,10 @@ impl Table {
     ///
     /// # Arguments
     /// * `pa` - partition
-    pub fn remove_partition(&self, pa: &mut Partition) -> Result<()> {
+    pub fn remove_partition(&mut self, pa: &Partition) -> Result<()> {
         match unsafe { fdisk_sys::fdisk_table_remove_partition(self.ptr, pa.ptr) } {
             0 => Ok(()),
-            v => Err(anyhow!(
-                "removing partition, errno: {}",
-                nix::errno::from_i32(v)
-            )),
+            v => Err(Error::fdisk("removing partition", v)),
         }
     }
 
@@ -101,7 +92,7 @@ impl Table {
         matches!(unsafe { fdisk_sys::fdisk_table_wrong_order(self.ptr) }, 1)
     }
 
-    pub fn iter(&mut self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter::new(self)
     }
 }
@@ -118,8 +109,17 @@ impl Default for Table {
     }
 }
 
+impl<'a> IntoIterator for &'a Table {
+    type Item = PartitionRef<'a>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl<'a> IntoIterator for &'a mut Table {
-    type Item = Partition;
+    type Item = PartitionRef<'a>;
     type IntoIter = Iter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -0,0 +1,39 @@
+use fdisk::{Ask, AskHandler, Context};
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+/// Sparse image file removed on drop
+struct Image(PathBuf);
+
+impl Image {
+    fn new(name: &str) -> Image {
+        let path = std::env::temp_dir().join(format!("fdisk-{}-{}.img", name, std::process::id()));
+        File::create(&path).unwrap().set_len(16 << 20).unwrap();
+        Image(path)
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+struct Panics;
+
+impl AskHandler for Panics {
+    fn ask(&mut self, _ask: Ask<'_>) -> fdisk::Result<()> {
+        panic!("handler panic");
+    }
+}
+
+#[test]
+fn handler_panic_does_not_abort() {
+    let img = Image::new("ask-panic");
+    let mut cxt = Context::new();
+    cxt.set_ask_handler(Panics).unwrap();
+    cxt.assign_device(&img.0, false).unwrap();
+    // the info message about the new label goes to the handler
+    cxt.create_disklabel("dos").unwrap();
+    assert!(cxt.has_label());
+}
@@ -0,0 +1,92 @@
+use fdisk::{BsdGeometry, Context};
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+/// Sparse image file removed on drop
+struct Image(PathBuf);
+
+impl Image {
+    fn new(name: &str) -> Image {
+        let path = std::env::temp_dir().join(format!("fdisk-{}-{}.img", name, std::process::id()));
+        File::create(&path).unwrap().set_len(64 << 20).unwrap();
+        Image(path)
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn bsd_functions_require_bsd_label() {
+    let img = Image::new("bsd-dos");
+    let cxt = Context::new();
+    cxt.assign_device(&img.0, false).unwrap();
+    cxt.create_disklabel("dos").unwrap();
+    assert!(cxt.bsd_geometry().is_err());
+    assert!(cxt.bsd_edit_disklabel(&BsdGeometry::default()).is_err());
+    assert!(cxt.bsd_write_bootstrap("sd").is_err());
+    assert!(cxt.bsd_link_partition(&cxt, 0, 0).is_err());
+}
+
+#[test]
+fn bsd_link_requires_bsd_label_in_nested_context() {
+    let img = Image::new("bsd-pmbr");
+    let cxt = Context::new();
+    cxt.assign_device(&img.0, false).unwrap();
+    cxt.create_disklabel("gpt").unwrap();
+    cxt.write_disklabel().unwrap();
+    // nested DOS context with parent
+    let pmbr = cxt.protective_mbr().unwrap();
+    assert!(pmbr.bsd_link_partition(&cxt, 0, 0).is_err());
+    assert!(pmbr.bsd_edit_disklabel(&BsdGeometry::default()).is_err());
+    assert!(pmbr.bsd_write_bootstrap("sd").is_err());
+}
+
+#[test]
+fn bsd_edit_checks_all_values_first() {
+    use fdisk::Partition;
+
+    let img = Image::new("bsd-edit");
+    let cxt = Context::new();
+    cxt.assign_device(&img.0, false).unwrap();
+    cxt.create_disklabel("dos").unwrap();
+    let lb = cxt.get_label("").unwrap().unwrap();
+    let pa = Partition::new();
+    pa.set_partno(0).unwrap();
+    pa.set_start(2048).unwrap();
+    pa.set_size(30000).unwrap();
+    pa.set_type(&lb.parttype_from_code(0xa5).unwrap()).unwrap();
+    cxt.add_partition(&pa).unwrap();
+    let bsd = cxt.create_bsd_disklabel(0).unwrap();
+
+    // the getter values are accepted
+    let fresh = bsd.bsd_geometry().unwrap();
+    bsd.bsd_edit_disklabel(&fresh).unwrap();
+    assert_eq!(bsd.bsd_geometry().unwrap(), fresh);
+
+    let mut g = fresh;
+    g.secpercyl = 1000;
+    g.rpm = 7200;
+    g.trackskew = 3;
+    bsd.bsd_edit_disklabel(&g).unwrap();
+    assert_eq!(bsd.bsd_geometry().unwrap(), g);
+    bsd.bsd_edit_disklabel(&g).unwrap();
+    assert_eq!(bsd.bsd_geometry().unwrap(), g);
+
+    // invalid value after changed one, nothing is changed
+    let mut bad = g;
+    bad.rpm = 5400;
+    bad.trackskew = 0;
+    assert!(bsd.bsd_edit_disklabel(&bad).is_err());
+    assert_eq!(bsd.bsd_geometry().unwrap(), g);
+    bad.trackskew = 3;
+    bad.secpercyl = 100000;
+    assert!(bsd.bsd_edit_disklabel(&bad).is_err());
+    bad.secpercyl = 1000;
+    bad.interleave = 0;
+    assert!(bsd.bsd_edit_disklabel(&bad).is_err());
+    assert_eq!(bsd.bsd_geometry().unwrap(), g);
+}
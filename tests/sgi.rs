@@ -0,0 +1,75 @@
+use fdisk::{Context, LabelItem, SgiVolume};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// Sparse image file removed on drop
+struct Image(PathBuf);
+
+impl Image {
+    fn new(name: &str) -> Image {
+        let path = std::env::temp_dir().join(format!("fdisk-{}-{}.img", name, std::process::id()));
+        File::create(&path).unwrap().set_len(64 << 20).unwrap();
+        Image(path)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn bootfile(cxt: &Context) -> Option<String> {
+    cxt.label_items()
+        .unwrap()
+        .into_iter()
+        .find_map(|item| match item {
+            LabelItem::SgiBootfile(path) => Some(path),
+            _ => None,
+        })
+}
+
+#[test]
+fn sgi_bootfile_and_info() {
+    let img = Image::new("sgi");
+    let cxt = Context::new();
+    cxt.assign_device(img.path(), false).unwrap();
+    cxt.create_disklabel("sgi").unwrap();
+    cxt.sgi_set_bootfile("/unix.save").unwrap();
+    assert!(cxt.sgi_set_bootfile("unix").is_err());
+    assert!(cxt.sgi_set_bootfile("/too-long-boot-file-name").is_err());
+    cxt.sgi_create_info().unwrap();
+    // the label is not on the device yet
+    assert!(cxt.sgi_ondisk_volumes().is_err());
+    cxt.write_disklabel().unwrap();
+
+    let sgilabel = SgiVolume {
+        name: "sgilabel".to_string(),
+        block: 2,
+        size: 512,
+    };
+    assert_eq!(cxt.sgi_ondisk_volumes().unwrap(), vec![sgilabel.clone()]);
+    assert_eq!(bootfile(&cxt).as_deref(), Some("/unix.save"));
+    drop(cxt);
+
+    let cxt = Context::new();
+    cxt.assign_device(img.path(), true).unwrap();
+    assert_eq!(cxt.sgi_ondisk_volumes().unwrap(), vec![sgilabel]);
+    assert_eq!(bootfile(&cxt).as_deref(), Some("/unix.save"));
+}
+
+#[test]
+fn sgi_requires_sgi_label() {
+    let img = Image::new("dos");
+    let cxt = Context::new();
+    assert!(cxt.sgi_ondisk_volumes().is_err());
+    cxt.assign_device(img.path(), false).unwrap();
+    cxt.create_disklabel("dos").unwrap();
+    assert!(cxt.sgi_set_bootfile("/unix").is_err());
+    assert!(cxt.sgi_create_info().is_err());
+    assert!(cxt.sgi_ondisk_volumes().is_err());
+}
@@ -0,0 +1,41 @@
+use fdisk::{Partition, Table};
+
+fn new_partition(partno: usize, start: u64, size: u64) -> Partition {
+    let pa = Partition::new();
+    pa.set_partno(partno).unwrap();
+    pa.set_start(start).unwrap();
+    pa.set_size(size).unwrap();
+    pa.set_attrs("RequiredPartition").unwrap();
+    pa
+}
+
+#[test]
+fn table_entries_outlive_table_only_as_owned() {
+    let mut tb = Table::new();
+    for (partno, start) in [(0, 2048), (1, 10240)].iter() {
+        // the table keeps the only reference
+        tb.add_partition(&new_partition(*partno, *start, 8192))
+            .unwrap();
+    }
+    assert_eq!(tb.nents(), 2);
+
+    // borrowed entries don't consume the table
+    for _ in 0..2 {
+        let starts: Vec<_> = tb.iter().map(|pa| pa.start()).collect();
+        assert_eq!(starts, vec![Some(2048), Some(10240)]);
+    }
+    let starts: Vec<_> = (&tb).into_iter().map(|pa| pa.start()).collect();
+    assert_eq!(starts, vec![Some(2048), Some(10240)]);
+
+    let pa = tb.partition_by_partno(1).unwrap().to_partition();
+    let first: Partition = tb.iter().next().unwrap().into();
+    drop(tb);
+
+    assert_eq!(pa.partno(), Some(1));
+    assert_eq!(pa.start(), Some(10240));
+    assert_eq!(pa.size(), Some(8192));
+    assert_eq!(pa.end(), Some(10240 + 8192 - 1));
+    assert_eq!(pa.attrs().unwrap().as_deref(), Some("RequiredPartition"));
+    assert_eq!(first.start(), Some(2048));
+    assert_eq!(Partition::new().attrs().unwrap(), None);
+}